@@ -0,0 +1,4 @@
+// migrations/ 中的脚本由 sqlx::migrate!() 编译进二进制，修改后需要重新编译
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
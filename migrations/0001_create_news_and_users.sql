@@ -0,0 +1,16 @@
+-- 初始表结构（与最初手工建库一致；已有数据库中表已存在时跳过）
+CREATE TABLE IF NOT EXISTS news (
+    id SERIAL PRIMARY KEY,
+    news_type VARCHAR(50) NOT NULL,
+    href TEXT NOT NULL,
+    title TEXT NOT NULL,
+    datetime TIMESTAMP NOT NULL DEFAULT now(),
+    content TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS users (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    password TEXT NOT NULL
+);
@@ -0,0 +1,15 @@
+-- 软删除、全文检索、按 href 幂等写入、用户角色
+ALTER TABLE news ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+ALTER TABLE news ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+CREATE INDEX IF NOT EXISTS news_search_vector_idx ON news USING GIN (search_vector);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'news_href_key') THEN
+        -- 旧数据可能有重复采集的记录，保留最早的一条
+        DELETE FROM news a USING news b WHERE a.href = b.href AND a.id > b.id;
+        ALTER TABLE news ADD CONSTRAINT news_href_key UNIQUE (href);
+    END IF;
+END $$;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'reader';
@@ -0,0 +1,25 @@
+-- 原始发布时间与入库时间分开保存，统一使用 TIMESTAMPTZ
+-- 旧的 datetime 列不带时区，按数据源的北京时间解释
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = 'news' AND column_name = 'datetime'
+    ) THEN
+        ALTER TABLE news RENAME COLUMN datetime TO published_at;
+        ALTER TABLE news ALTER COLUMN published_at DROP DEFAULT;
+        ALTER TABLE news ALTER COLUMN published_at TYPE TIMESTAMPTZ
+            USING published_at AT TIME ZONE 'Asia/Shanghai';
+        ALTER TABLE news ADD COLUMN ingested_at TIMESTAMPTZ NOT NULL DEFAULT now();
+        UPDATE news SET ingested_at = published_at;
+    END IF;
+
+    -- deleted_at 由数据库 now() 写入，旧值按 UTC 解释
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = 'news'
+          AND column_name = 'deleted_at' AND data_type = 'timestamp without time zone'
+    ) THEN
+        ALTER TABLE news ALTER COLUMN deleted_at TYPE TIMESTAMPTZ USING deleted_at AT TIME ZONE 'UTC';
+    END IF;
+END $$;
@@ -0,0 +1,13 @@
+-- 浏览量（popular 排序）、列表查询索引、用户邮箱唯一
+ALTER TABLE news ADD COLUMN IF NOT EXISTS view_count BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS news_published_at_id_idx ON news (published_at, id) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS news_ingested_at_id_idx ON news (ingested_at, id) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS news_news_type_published_at_idx ON news (news_type, published_at) WHERE deleted_at IS NULL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'users_email_key') THEN
+        ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+    END IF;
+END $$;
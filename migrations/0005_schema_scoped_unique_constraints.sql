@@ -0,0 +1,19 @@
+-- 0002/0004 按约束名在整个数据库中判断约束是否存在，在非 public 的 schema 中建表（如测试用的临时 schema）时会被跳过；
+-- 这里只在当前 schema 中检查，已有数据库不受影响
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'news_href_key' AND connamespace = current_schema()::regnamespace
+    ) THEN
+        DELETE FROM news a USING news b WHERE a.href = b.href AND a.id > b.id;
+        ALTER TABLE news ADD CONSTRAINT news_href_key UNIQUE (href);
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'users_email_key' AND connamespace = current_schema()::regnamespace
+    ) THEN
+        ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+    END IF;
+END $$;
@@ -0,0 +1,13 @@
+-- 服务端会话（session.store = "postgres"）：cookie 中只保存 session_key，便于列出和撤销登录会话
+CREATE TABLE IF NOT EXISTS sessions (
+    id BIGSERIAL PRIMARY KEY,
+    session_key TEXT NOT NULL UNIQUE,
+    user_id INTEGER REFERENCES users (id) ON DELETE CASCADE,
+    state JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS sessions_user_id_idx ON sessions (user_id);
+CREATE INDEX IF NOT EXISTS sessions_expires_at_idx ON sessions (expires_at);
@@ -0,0 +1,11 @@
+-- 用户收藏：同一用户对同一新闻只收藏一次；用户或新闻被物理删除时一并删除
+CREATE TABLE IF NOT EXISTS bookmarks (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    news_id INTEGER NOT NULL REFERENCES news (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, news_id)
+);
+
+-- 收藏列表按收藏时间倒序分页
+CREATE INDEX IF NOT EXISTS bookmarks_user_created_idx ON bookmarks (user_id, created_at DESC, news_id DESC);
+CREATE INDEX IF NOT EXISTS bookmarks_news_id_idx ON bookmarks (news_id);
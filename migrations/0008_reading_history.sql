@@ -0,0 +1,13 @@
+-- 阅读记录：登录用户打开新闻详情时写入，同一篇只保留一行并累计次数
+CREATE TABLE IF NOT EXISTS reading_history (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    news_id INTEGER NOT NULL REFERENCES news (id) ON DELETE CASCADE,
+    first_read_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_read_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    read_count INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (user_id, news_id)
+);
+
+-- 阅读记录按最近阅读时间倒序分页
+CREATE INDEX IF NOT EXISTS reading_history_user_last_read_idx ON reading_history (user_id, last_read_at DESC, news_id DESC);
+CREATE INDEX IF NOT EXISTS reading_history_news_id_idx ON reading_history (news_id);
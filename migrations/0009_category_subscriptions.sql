@@ -0,0 +1,7 @@
+-- 用户订阅的新闻分类，用于 /news/feed 个性化信息流
+CREATE TABLE IF NOT EXISTS category_subscriptions (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    category TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, category)
+);
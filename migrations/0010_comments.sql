@@ -0,0 +1,23 @@
+-- 新闻评论：parent_id 为回复的评论，root_id 为所在讨论串的顶层评论（顶层评论为 NULL）
+-- status：pending 待审核（公开可见），approved 已审核，hidden 被编辑隐藏
+CREATE TABLE IF NOT EXISTS comments (
+    id SERIAL PRIMARY KEY,
+    news_id INTEGER NOT NULL REFERENCES news (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    parent_id INTEGER REFERENCES comments (id) ON DELETE CASCADE,
+    root_id INTEGER REFERENCES comments (id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'hidden')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    deleted_at TIMESTAMPTZ
+);
+
+-- 按新闻分页列出顶层评论
+CREATE INDEX IF NOT EXISTS comments_news_root_idx ON comments (news_id, created_at DESC, id DESC) WHERE root_id IS NULL;
+-- 取讨论串中的全部回复
+CREATE INDEX IF NOT EXISTS comments_root_id_idx ON comments (root_id);
+CREATE INDEX IF NOT EXISTS comments_parent_id_idx ON comments (parent_id);
+CREATE INDEX IF NOT EXISTS comments_user_id_idx ON comments (user_id);
+-- 审核队列
+CREATE INDEX IF NOT EXISTS comments_status_idx ON comments (status, created_at) WHERE deleted_at IS NULL;
@@ -0,0 +1,13 @@
+-- 新闻表态：每个用户对每篇新闻只保留一个表态（点赞或其他表情），再次表态会替换原表态
+CREATE TABLE IF NOT EXISTS reactions (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    news_id INTEGER NOT NULL REFERENCES news (id) ON DELETE CASCADE,
+    reaction VARCHAR(16) NOT NULL CHECK (reaction IN ('like', 'love', 'haha', 'wow', 'sad', 'angry')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, news_id)
+);
+
+-- 列表中按新闻统计各表态数量
+CREATE INDEX IF NOT EXISTS reactions_news_id_idx ON reactions (news_id, reaction);
+-- 最近 N 天点赞最多
+CREATE INDEX IF NOT EXISTS reactions_reaction_created_idx ON reactions (reaction, created_at);
@@ -0,0 +1,10 @@
+-- 按小时汇总的新闻浏览量，用于计算时间窗口内的热度（news.view_count 仍为累计浏览量）
+CREATE TABLE IF NOT EXISTS news_views (
+    news_id INTEGER NOT NULL REFERENCES news (id) ON DELETE CASCADE,
+    hour TIMESTAMPTZ NOT NULL,
+    views BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (news_id, hour)
+);
+
+-- 按时间窗口汇总浏览量
+CREATE INDEX IF NOT EXISTS news_views_hour_idx ON news_views (hour);
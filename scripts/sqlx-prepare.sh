@@ -0,0 +1,48 @@
+#!/usr/bin/env bash
+# 生成/校验 sqlx 离线查询缓存（.sqlx/），效果等同于 `cargo sqlx prepare [--check]`，不依赖 sqlx-cli
+#
+#   scripts/sqlx-prepare.sh          用 DATABASE_URL 指向的数据库重新生成 .sqlx/
+#   scripts/sqlx-prepare.sh --check  新建临时数据库并执行 migrations/，重新生成缓存后与 .sqlx/ 比较，
+#                                    不一致时返回非零（缓存过期，或 SQL 与迁移后的表结构不匹配）
+#
+# 提交 .sqlx/ 后，未设置 DATABASE_URL（或设置 SQLX_OFFLINE=true）时即可离线编译。
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+: "${DATABASE_URL:?DATABASE_URL must be set}"
+
+# 重新展开 query! 宏，把查询元数据写入 $1
+generate() {
+    rm -rf "$1"
+    mkdir -p "$1"
+    cargo clean -p news-backend
+    SQLX_OFFLINE=false SQLX_OFFLINE_DIR="$1" cargo check --workspace --all-targets
+}
+
+if [[ "${1:-}" != "--check" ]]; then
+    generate "$PWD/.sqlx"
+    echo "Query metadata written to .sqlx/"
+    exit 0
+fi
+
+check_db="news_sqlx_check_$$"
+server_url="${DATABASE_URL%/*}"
+check_url="$server_url/$check_db"
+tmp_dir="$(mktemp -d)"
+cleanup() {
+    psql -q "$server_url/postgres" -c "DROP DATABASE IF EXISTS $check_db" || true
+    rm -rf "$tmp_dir"
+}
+trap cleanup EXIT
+
+psql -q "$server_url/postgres" -c "CREATE DATABASE $check_db"
+# 迁移程序本身用现有缓存离线编译
+SQLX_OFFLINE=true DATABASE_URL="$check_url" cargo run --quiet --bin news-backend -- --migrate
+DATABASE_URL="$check_url" generate "$tmp_dir/.sqlx"
+
+if diff -r .sqlx "$tmp_dir/.sqlx"; then
+    echo ".sqlx/ is up to date with migrations/"
+else
+    echo ".sqlx/ is out of date, run scripts/sqlx-prepare.sh" >&2
+    exit 1
+fi
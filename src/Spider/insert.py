@@ -12,17 +12,31 @@ conn = psycopg2.connect(
 # 创建游标
 cur = conn.cursor()
 
-# 执行COPY命令导入CSV
+# 先COPY到临时表，再按 href 幂等写入（与 NewsRepo::upsert_news 规则一致），
+# 重复导入不会产生重复记录或主键冲突，id 由数据库序列生成
 try:
+    # CSV 中的发布时间不带时区，按北京时间（UTC+8）解释
+    cur.execute("SET TIME ZONE 'Asia/Shanghai';")
+    cur.execute("CREATE TEMP TABLE news_import (LIKE news INCLUDING DEFAULTS) ON COMMIT DROP;")
     with open("news_data.csv", "r", encoding="utf-8-sig") as f:
         # 跳过CSV标题行（如果CSV有标题）
         next(f)  
         cur.copy_expert(
-            sql.SQL("COPY news (id, news_type, href, title, datetime, content) FROM STDIN WITH (FORMAT CSV)"),
+            sql.SQL("COPY news_import (id, news_type, href, title, published_at, content) FROM STDIN WITH (FORMAT CSV)"),
             f
         )
-    # 更新序列避免后续主键冲突
-    cur.execute("SELECT setval('news_id_seq', (SELECT MAX(id) FROM news));")
+    cur.execute("""
+        INSERT INTO news (news_type, href, title, published_at, content)
+        SELECT DISTINCT ON (href) news_type, href, title, published_at, content FROM news_import
+        ON CONFLICT (href) DO UPDATE SET
+            title = EXCLUDED.title,
+            content = EXCLUDED.content,
+            published_at = EXCLUDED.published_at,
+            search_vector = NULL  -- 由后端启动时重建检索向量
+        WHERE (news.title, news.content, news.published_at)
+            IS DISTINCT FROM (EXCLUDED.title, EXCLUDED.content, EXCLUDED.published_at);
+    """)
+    print("写入/更新条数:", cur.rowcount)
     conn.commit()
     print("导入成功！")
 except Exception as e:
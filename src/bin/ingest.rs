@@ -0,0 +1,63 @@
+use dotenv::dotenv;
+use env_logger::Builder;
+use std::env;
+
+use news_backend::db::migrate::check_schema;
+use news_backend::db::pool::init_pool;
+use news_backend::ingest::{Crawler, TencentSource, CATEGORY_CHANNELS};
+use news_backend::{Config, NewsDao, NewsRepo, NewsService, UsersRepo};
+
+// 采集腾讯新闻各分类并写入数据库
+// 用法：cargo run --bin ingest [--config 配置文件] [分类名 ...]，不传分类时采集全部分类
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    // 加载环境变量和配置（与服务端共用同一份配置，使用其中的 database、log、ingest 段）
+    dotenv().ok();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 初始化日志
+    Builder::new()
+       .parse_filters(&config.log.level)
+       .init();
+
+    let pool = init_pool(&config.database).await.expect("Failed to create DB pool");
+    // 与服务端一样，数据库结构与本次构建不一致时拒绝采集（提示先执行 --migrate）
+    check_schema(&pool).await.map_err(std::io::Error::other)?;
+    let news_service = NewsService::new(NewsDao::new(NewsRepo::new(pool.clone())), UsersRepo::new(pool));
+
+    // 数据源配置：cookie 等敏感信息建议只通过环境变量（TENCENT_COOKIE）设置
+    let ingest = &config.ingest;
+    let source = TencentSource::new(&ingest.feed_url, ingest.cookie.as_deref())
+        .expect("Failed to create Tencent source")
+        .with_device_id(&ingest.device_id);
+    let options = ingest.crawl_options();
+
+    // 只采集命令行指定的分类（跳过 --config 及其参数）
+    let mut args = env::args().skip(1);
+    let mut wanted: Vec<String> = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            args.next();
+        } else {
+            wanted.push(arg);
+        }
+    }
+    let channels: Vec<(&str, &str)> = CATEGORY_CHANNELS
+        .iter()
+        .copied()
+        .filter(|(category, _)| wanted.is_empty() || wanted.iter().any(|w| w == category))
+        .collect();
+
+    let report = Crawler::new(source, news_service, options).run(&channels).await;
+    log::info!(
+        "采集完成：新增 {} 条，更新 {} 条，未变化 {} 条，失败 {} 次",
+        report.inserted, report.updated, report.unchanged, report.failed
+    );
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::db::migrate::run_migrations;
+use crate::db::models::UpsertOutcome;
+use crate::db::queries;
+use crate::news::time::parse_datetime;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{Connection, Error, Executor, PgConnection, PgPool};
+use std::env;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// 固定测试数据：爬虫导出的新闻样本
+const SEED_CSV: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/Spider/news_data.csv");
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+// 测试数据库夹具：每个测试独占一个临时 schema（已执行全部迁移），
+// 连接池的 search_path 只指向该 schema；结束时（包括测试 panic）删除整个 schema
+pub struct TestDb {
+    pub pool: PgPool,
+    schema: String,
+    url: String,
+}
+
+impl TestDb {
+    pub async fn new() -> TestDb {
+        dotenv::dotenv().ok();
+        let url = env::var("DATABASE_URL").expect("DATABASE_URL must be set for database tests");
+        let schema = format!("test_{}_{}", std::process::id(), NEXT_ID.fetch_add(1, Ordering::Relaxed));
+
+        let mut conn = PgConnection::connect(&url).await.expect("Failed to connect to test database");
+        // 上次运行被强行中断时可能残留同名 schema
+        conn.execute(format!("DROP SCHEMA IF EXISTS {0} CASCADE; CREATE SCHEMA {0}", schema).as_str())
+            .await
+            .expect("Failed to create test schema");
+        conn.close().await.ok();
+
+        // application_name 用于清理时找到本夹具的连接
+        let options = url
+            .parse::<PgConnectOptions>()
+            .expect("Invalid DATABASE_URL")
+            .application_name(&schema)
+            .options([("search_path", schema.as_str())]);
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await
+            .expect("Failed to connect to test schema");
+        run_migrations(&pool).await.expect("Failed to migrate test schema");
+
+        TestDb { pool, schema, url }
+    }
+
+    // 迁移后再导入 news_data.csv
+    pub async fn seeded() -> TestDb {
+        let db = TestDb::new().await;
+        let mut tx = db.pool.begin().await.unwrap();
+        seed_news(&mut tx).await.expect("Failed to load seed data");
+        tx.commit().await.unwrap();
+        db
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let (url, schema) = (self.url.clone(), self.schema.clone());
+        // Drop 中不能 await，在独立线程的运行时中清理；
+        // 先断开本夹具的连接（可能留有未结束的事务），再删除 schema
+        let cleanup = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            runtime.block_on(async {
+                let mut conn = PgConnection::connect(&url).await?;
+                sqlx::query("SELECT pg_terminate_backend(pid) FROM pg_stat_activity WHERE application_name = $1")
+                    .bind(&schema)
+                    .execute(&mut conn)
+                    .await?;
+                conn.execute(format!("DROP SCHEMA IF EXISTS {} CASCADE", schema).as_str()).await?;
+                Ok::<_, Error>(())
+            })
+        });
+        if let Ok(Err(e)) = cleanup.join() {
+            eprintln!("Failed to drop test schema {}: {}", self.schema, e);
+        }
+    }
+}
+
+// 导入 news_data.csv（id,news_type,href,title,datetime,content），时间按北京时间解释。
+// 同一篇文章可能出现在多个分类下，与爬虫一样按 href 去重，返回新增条数
+pub async fn seed_news(conn: &mut PgConnection) -> Result<u64, Error> {
+    let mut reader = csv::Reader::from_path(SEED_CSV).expect("Failed to open news_data.csv");
+    let mut count = 0;
+    for record in reader.records() {
+        let record = record.expect("Malformed row in news_data.csv");
+        let outcome = queries::upsert_news(
+            &mut *conn,
+            &record[1],
+            &record[2],
+            &record[3],
+            &record[5],
+            parse_datetime(&record[4]),
+        )
+        .await?;
+        if matches!(outcome, UpsertOutcome::Inserted(_)) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
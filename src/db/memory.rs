@@ -0,0 +1,998 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use chrono::{DateTime, Duration, DurationRound, SubsecRound, Utc};
+use sqlx::error::{DatabaseError, ErrorKind};
+use sqlx::Error;
+
+use crate::db::models::{
+    CommentRow, CommentStatus, HistoryRow, Keyset, LikedNewsRow, NewsFilter, NewsModel, NewsSearchRow, NewsSort, ReactionCount,
+    ReactionKind, TimeField, TrendingNewsRow, UpsertOutcome, UserModel, UserReaction, UserRole,
+};
+use crate::db::models::hotness;
+use crate::db::store::{NewsStore, UserStore};
+use crate::db::tokenizer::tokenize;
+
+// 内存中的新闻记录，附带数据库中不随 NewsModel 返回的列
+#[derive(Debug, Clone)]
+struct NewsRow {
+    news: NewsModel,
+    deleted: bool, // deleted_at IS NOT NULL
+    view_count: i64,
+}
+
+// 按小时汇总的浏览量
+#[derive(Debug, Clone)]
+struct ViewBucket {
+    news_id: i32,
+    hour: DateTime<Utc>,
+    views: i64,
+}
+
+#[derive(Debug, Clone)]
+struct Bookmark {
+    user_id: i32,
+    news_id: i32,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct Reaction {
+    user_id: i32,
+    news_id: i32,
+    reaction: ReactionKind,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    user_id: i32,
+    news_id: i32,
+    last_read_at: DateTime<Utc>,
+    read_count: i32,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    news: Vec<NewsRow>,
+    users: Vec<UserModel>,
+    bookmarks: Vec<Bookmark>,
+    history: Vec<HistoryEntry>,
+    subscriptions: Vec<(i32, String)>, // (user_id, category)
+    comments: Vec<CommentRow>,
+    reactions: Vec<Reaction>,
+    views: Vec<ViewBucket>,
+    next_comment_id: i32,
+    next_news_id: i32,
+    next_user_id: i32,
+}
+
+// 内存存储：与 Postgres 仓库实现同样的接口和约束（href、email 唯一，软删除等），
+// 用于不依赖数据库的 service / 路由测试；克隆后共享同一份数据
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("memory store lock poisoned")
+    }
+}
+
+// 与 Postgres 的 TIMESTAMPTZ 一致，只保留到微秒
+fn db_time(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.trunc_subsecs(6)
+}
+
+// 唯一约束冲突；与 Postgres 返回的错误一样会被 NewsError 转为 409
+#[derive(Debug)]
+struct UniqueViolation {
+    constraint: &'static str,
+    message: String,
+}
+
+impl fmt::Display for UniqueViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for UniqueViolation {}
+
+impl DatabaseError for UniqueViolation {
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        Some(self.constraint)
+    }
+
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::UniqueViolation
+    }
+}
+
+fn unique_violation(constraint: &'static str) -> Error {
+    Error::Database(Box::new(UniqueViolation {
+        constraint,
+        message: format!("duplicate key value violates unique constraint \"{}\"", constraint),
+    }))
+}
+
+fn time_of(news: &NewsModel, field: TimeField) -> DateTime<Utc> {
+    match field {
+        TimeField::Published => news.published_at,
+        TimeField::Ingested => news.ingested_at,
+    }
+}
+
+// 对应 repo::push_news_filter（unread_by 需要阅读记录，见 State::matches）
+fn matches_filter(row: &NewsRow, filter: &NewsFilter) -> bool {
+    let news = &row.news;
+    let time = time_of(news, filter.time_field);
+    !row.deleted
+        && (filter.categories.is_empty() || filter.categories.contains(&news.news_type))
+        && !filter.exclude_categories.contains(&news.news_type)
+        && !filter.exclude_ids.contains(&news.id)
+        && filter.from.is_none_or(|from| time >= from)
+        && filter.to.is_none_or(|to| time < to)
+}
+
+// 对应 repo::push_news_order
+fn compare(a: &NewsRow, b: &NewsRow, sort: NewsSort, field: TimeField) -> Ordering {
+    let (ta, tb) = (time_of(&a.news, field), time_of(&b.news, field));
+    match sort {
+        NewsSort::Newest => tb.cmp(&ta).then(b.news.id.cmp(&a.news.id)),
+        NewsSort::Oldest => ta.cmp(&tb).then(a.news.id.cmp(&b.news.id)),
+        NewsSort::Title => a.news.title.cmp(&b.news.title).then(a.news.id.cmp(&b.news.id)),
+        NewsSort::Popular => b
+            .view_count
+            .cmp(&a.view_count)
+            .then(tb.cmp(&ta))
+            .then(b.news.id.cmp(&a.news.id)),
+    }
+}
+
+// 检索得分：所有检索词都需命中，按 ts_rank 的默认权重近似（标题 1.0，正文 0.4）
+fn search_rank(news: &NewsModel, terms: &[String]) -> Option<f32> {
+    if terms.is_empty() {
+        return None;
+    }
+    let (title, content) = (tokenize(&news.title), tokenize(&news.content));
+    terms.iter().try_fold(0.0, |rank, term| {
+        let in_title = title.iter().filter(|t| *t == term).count() as f32;
+        let in_content = content.iter().filter(|t| *t == term).count() as f32;
+        (in_title + in_content > 0.0).then_some(rank + in_title + 0.4 * in_content)
+    })
+}
+
+// 顶层评论按发表时间倒序，讨论串内按正序
+fn comment_order(a: &CommentRow, b: &CommentRow) -> Ordering {
+    a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id))
+}
+
+impl State {
+    // 对应 repo::list_comment_threads 中顶层评论的条件
+    fn comment_roots(&self, news_id: i32) -> Vec<&CommentRow> {
+        let mut roots: Vec<&CommentRow> = self
+            .comments
+            .iter()
+            .filter(|c| c.news_id == news_id && c.root_id.is_none())
+            .filter(|c| c.is_visible() || self.comments.iter().any(|r| r.root_id == Some(c.id) && r.is_visible()))
+            .collect();
+        roots.sort_by(|a, b| comment_order(b, a));
+        roots
+    }
+
+    fn matches(&self, row: &NewsRow, filter: &NewsFilter) -> bool {
+        matches_filter(row, filter)
+            && filter
+                .unread_by
+                .is_none_or(|user_id| !self.history.iter().any(|h| h.user_id == user_id && h.news_id == row.news.id))
+    }
+
+    fn visible(&self, id: i32) -> Option<&NewsRow> {
+        self.news.iter().find(|row| row.news.id == id && !row.deleted)
+    }
+
+    fn visible_mut(&mut self, id: i32) -> Option<&mut NewsRow> {
+        self.news.iter_mut().find(|row| row.news.id == id && !row.deleted)
+    }
+
+    fn insert_news(
+        &mut self,
+        news_type: &str,
+        href: &str,
+        title: &str,
+        content: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<NewsModel, Error> {
+        if self.news.iter().any(|row| row.news.href == href) {
+            return Err(unique_violation("news_href_key"));
+        }
+        self.next_news_id += 1;
+        let now = db_time(Utc::now());
+        let news = NewsModel {
+            id: self.next_news_id,
+            news_type: news_type.to_string(),
+            href: href.to_string(),
+            title: title.to_string(),
+            published_at: published_at.map(db_time).unwrap_or(now),
+            ingested_at: now,
+            content: content.to_string(),
+        };
+        self.news.push(NewsRow { news: news.clone(), deleted: false, view_count: 0 });
+        Ok(news)
+    }
+
+    fn search(&self, terms: &[String], category: Option<&str>) -> Vec<NewsSearchRow> {
+        let mut rows: Vec<NewsSearchRow> = self
+            .news
+            .iter()
+            .filter(|row| !row.deleted && category.is_none_or(|c| row.news.news_type == c))
+            .filter_map(|row| {
+                let news = row.news.clone();
+                search_rank(&news, terms).map(|rank| NewsSearchRow {
+                    id: news.id,
+                    news_type: news.news_type,
+                    href: news.href,
+                    title: news.title,
+                    published_at: news.published_at,
+                    ingested_at: news.ingested_at,
+                    content: news.content,
+                    rank,
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(b.published_at.cmp(&a.published_at)));
+        rows
+    }
+}
+
+#[async_trait]
+impl NewsStore for MemoryStore {
+    async fn create_news(
+        &self,
+        news_type: &str,
+        href: &str,
+        title: &str,
+        content: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<NewsModel, Error> {
+        self.state().insert_news(news_type, href, title, content, published_at)
+    }
+
+    async fn upsert_news(
+        &self,
+        news_type: &str,
+        href: &str,
+        title: &str,
+        content: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<UpsertOutcome, Error> {
+        let mut state = self.state();
+        // 与 ON CONFLICT (href) 一致：已软删除的记录同样按 href 命中
+        let Some(row) = state.news.iter_mut().find(|row| row.news.href == href) else {
+            let news = state.insert_news(news_type, href, title, content, published_at)?;
+            return Ok(UpsertOutcome::Inserted(news));
+        };
+
+        let published_at = published_at.map(db_time).unwrap_or(row.news.published_at);
+        if row.news.title == title && row.news.content == content && row.news.published_at == published_at {
+            return Ok(UpsertOutcome::Unchanged);
+        }
+        row.news.title = title.to_string();
+        row.news.content = content.to_string();
+        row.news.published_at = published_at;
+        Ok(UpsertOutcome::Updated(row.news.clone()))
+    }
+
+    async fn get_news_by_id(&self, id: i32) -> Result<Option<NewsModel>, Error> {
+        Ok(self.state().visible(id).map(|row| row.news.clone()))
+    }
+
+    async fn get_related_news(&self, news: &NewsModel, limit: u32) -> Result<Vec<NewsModel>, Error> {
+        let state = self.state();
+        let distance = |n: &NewsModel| (n.published_at - news.published_at).num_microseconds().unwrap_or(i64::MAX).abs();
+        let mut related: Vec<NewsModel> = state
+            .news
+            .iter()
+            .filter(|row| !row.deleted && row.news.news_type == news.news_type && row.news.id != news.id)
+            .map(|row| row.news.clone())
+            .collect();
+        related.sort_by(|a, b| distance(a).cmp(&distance(b)).then(b.id.cmp(&a.id)));
+        related.truncate(limit as usize);
+        Ok(related)
+    }
+
+    async fn update_news(
+        &self,
+        id: i32,
+        news_type: Option<&str>,
+        href: Option<&str>,
+        title: Option<&str>,
+        content: Option<&str>,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<NewsModel>, Error> {
+        let mut state = self.state();
+        if let Some(href) = href {
+            if state.news.iter().any(|row| row.news.href == href && row.news.id != id) {
+                return Err(unique_violation("news_href_key"));
+            }
+        }
+        let Some(row) = state.visible_mut(id) else {
+            return Ok(None);
+        };
+        let news = &mut row.news;
+        if let Some(news_type) = news_type {
+            news.news_type = news_type.to_string();
+        }
+        if let Some(href) = href {
+            news.href = href.to_string();
+        }
+        if let Some(title) = title {
+            news.title = title.to_string();
+        }
+        if let Some(content) = content {
+            news.content = content.to_string();
+        }
+        if let Some(published_at) = published_at {
+            news.published_at = db_time(published_at);
+        }
+        Ok(Some(news.clone()))
+    }
+
+    // 检索时直接对标题和正文分词，不需要预先建立索引
+    async fn reindex_missing_search_vectors(&self, _batch_size: u32) -> Result<u64, Error> {
+        Ok(0)
+    }
+
+    async fn search_news(
+        &self,
+        terms: &[String],
+        category: Option<&str>,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Vec<NewsSearchRow>, Error> {
+        Ok(self
+            .state()
+            .search(terms, category)
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn search_news_count(&self, terms: &[String], category: Option<&str>) -> Result<i64, Error> {
+        Ok(self.state().search(terms, category).len() as i64)
+    }
+
+    async fn soft_delete_news(&self, id: i32) -> Result<bool, Error> {
+        Ok(match self.state().visible_mut(id) {
+            Some(row) => {
+                row.deleted = true;
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn restore_news(&self, id: i32) -> Result<Option<NewsModel>, Error> {
+        let mut state = self.state();
+        let row = state.news.iter_mut().find(|row| row.news.id == id && row.deleted);
+        Ok(row.map(|row| {
+            row.deleted = false;
+            row.news.clone()
+        }))
+    }
+
+    async fn query_news(
+        &self,
+        filter: &NewsFilter,
+        sort: NewsSort,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Vec<NewsModel>, Error> {
+        let state = self.state();
+        let mut rows: Vec<&NewsRow> = state.news.iter().filter(|row| state.matches(row, filter)).collect();
+        rows.sort_by(|a, b| compare(a, b, sort, filter.time_field));
+        Ok(rows
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit as usize)
+            .map(|row| row.news.clone())
+            .collect())
+    }
+
+    fn stream_news(&self, filter: NewsFilter) -> BoxStream<'static, Result<NewsModel, Error>> {
+        let state = self.state();
+        let mut rows: Vec<&NewsRow> = state.news.iter().filter(|row| state.matches(row, &filter)).collect();
+        rows.sort_by(|a, b| compare(a, b, NewsSort::Oldest, filter.time_field));
+        let news: Vec<NewsModel> = rows.into_iter().map(|row| row.news.clone()).collect();
+        stream::iter(news.into_iter().map(Ok)).boxed()
+    }
+
+    async fn query_news_keyset(
+        &self,
+        filter: &NewsFilter,
+        descending: bool,
+        keyset: Option<&Keyset>,
+        limit: u32,
+    ) -> Result<(Vec<NewsModel>, bool), Error> {
+        let backward = matches!(keyset, Some(Keyset::Before(_)));
+        let scan_desc = descending != backward;
+        let key = |n: &NewsModel| (time_of(n, filter.time_field), n.id);
+
+        let state = self.state();
+        let mut news: Vec<NewsModel> = state
+            .news
+            .iter()
+            .filter(|row| state.matches(row, filter))
+            .map(|row| row.news.clone())
+            .filter(|n| match keyset {
+                Some(Keyset::After(cursor) | Keyset::Before(cursor)) => {
+                    let position = (cursor.time, cursor.id);
+                    if scan_desc { key(n) < position } else { key(n) > position }
+                }
+                None => true,
+            })
+            .collect();
+        news.sort_by_key(key);
+        if scan_desc {
+            news.reverse();
+        }
+
+        let has_more = news.len() > limit as usize;
+        news.truncate(limit as usize);
+        if backward {
+            news.reverse();
+        }
+        Ok((news, has_more))
+    }
+
+    async fn get_news_count(&self, filter: &NewsFilter) -> Result<i64, Error> {
+        let state = self.state();
+        Ok(state.news.iter().filter(|row| state.matches(row, filter)).count() as i64)
+    }
+
+    async fn increment_view_count(&self, id: i32) -> Result<(), Error> {
+        let mut state = self.state();
+        let Some(row) = state.visible_mut(id) else { return Ok(()) };
+        row.view_count += 1;
+        let hour = Utc::now().duration_trunc(Duration::hours(1)).expect("hour fits in a timestamp");
+        match state.views.iter_mut().find(|b| b.news_id == id && b.hour == hour) {
+            Some(bucket) => bucket.views += 1,
+            None => state.views.push(ViewBucket { news_id: id, hour, views: 1 }),
+        }
+        Ok(())
+    }
+
+    async fn add_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        let mut state = self.state();
+        if state.visible(news_id).is_none()
+            || state.bookmarks.iter().any(|b| b.user_id == user_id && b.news_id == news_id)
+        {
+            return Ok(false);
+        }
+        state.bookmarks.push(Bookmark { user_id, news_id, created_at: db_time(Utc::now()) });
+        Ok(true)
+    }
+
+    async fn remove_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        let mut state = self.state();
+        let before = state.bookmarks.len();
+        state.bookmarks.retain(|b| !(b.user_id == user_id && b.news_id == news_id));
+        Ok(state.bookmarks.len() < before)
+    }
+
+    async fn list_bookmarks(&self, user_id: i32, limit: u32, offset: i64) -> Result<Vec<NewsModel>, Error> {
+        let state = self.state();
+        let mut bookmarks: Vec<&Bookmark> = state.bookmarks.iter().filter(|b| b.user_id == user_id).collect();
+        bookmarks.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.news_id.cmp(&a.news_id)));
+        Ok(bookmarks
+            .into_iter()
+            .filter_map(|b| state.visible(b.news_id))
+            .skip(offset.max(0) as usize)
+            .take(limit as usize)
+            .map(|row| row.news.clone())
+            .collect())
+    }
+
+    async fn count_bookmarks(&self, user_id: i32) -> Result<i64, Error> {
+        let state = self.state();
+        Ok(state
+            .bookmarks
+            .iter()
+            .filter(|b| b.user_id == user_id && state.visible(b.news_id).is_some())
+            .count() as i64)
+    }
+
+    async fn bookmarked_ids(&self, user_id: i32, news_ids: &[i32]) -> Result<Vec<i32>, Error> {
+        Ok(self
+            .state()
+            .bookmarks
+            .iter()
+            .filter(|b| b.user_id == user_id && news_ids.contains(&b.news_id))
+            .map(|b| b.news_id)
+            .collect())
+    }
+
+    async fn record_read(&self, user_id: i32, news_id: i32) -> Result<(), Error> {
+        let mut state = self.state();
+        let now = db_time(Utc::now());
+        match state.history.iter_mut().find(|h| h.user_id == user_id && h.news_id == news_id) {
+            Some(entry) => {
+                entry.last_read_at = now;
+                entry.read_count += 1;
+            }
+            None => state.history.push(HistoryEntry { user_id, news_id, last_read_at: now, read_count: 1 }),
+        }
+        Ok(())
+    }
+
+    async fn list_history(&self, user_id: i32, limit: u32, offset: i64) -> Result<Vec<HistoryRow>, Error> {
+        let state = self.state();
+        let mut entries: Vec<&HistoryEntry> = state.history.iter().filter(|h| h.user_id == user_id).collect();
+        entries.sort_by(|a, b| b.last_read_at.cmp(&a.last_read_at).then(b.news_id.cmp(&a.news_id)));
+        Ok(entries
+            .into_iter()
+            .filter_map(|h| state.visible(h.news_id).map(|row| (h, &row.news)))
+            .skip(offset.max(0) as usize)
+            .take(limit as usize)
+            .map(|(h, news)| HistoryRow {
+                id: news.id,
+                news_type: news.news_type.clone(),
+                href: news.href.clone(),
+                title: news.title.clone(),
+                published_at: news.published_at,
+                ingested_at: news.ingested_at,
+                content: news.content.clone(),
+                last_read_at: h.last_read_at,
+                read_count: h.read_count,
+            })
+            .collect())
+    }
+
+    async fn count_history(&self, user_id: i32) -> Result<i64, Error> {
+        let state = self.state();
+        Ok(state
+            .history
+            .iter()
+            .filter(|h| h.user_id == user_id && state.visible(h.news_id).is_some())
+            .count() as i64)
+    }
+
+    async fn remove_history(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        let mut state = self.state();
+        let before = state.history.len();
+        state.history.retain(|h| !(h.user_id == user_id && h.news_id == news_id));
+        Ok(state.history.len() < before)
+    }
+
+    async fn clear_history(&self, user_id: i32) -> Result<u64, Error> {
+        let mut state = self.state();
+        let before = state.history.len();
+        state.history.retain(|h| h.user_id != user_id);
+        Ok((before - state.history.len()) as u64)
+    }
+
+    async fn create_comment(
+        &self,
+        news_id: i32,
+        user_id: i32,
+        parent_id: Option<i32>,
+        root_id: Option<i32>,
+        content: &str,
+    ) -> Result<CommentRow, Error> {
+        let mut state = self.state();
+        let author = state
+            .users
+            .iter()
+            .find(|u| u.id == user_id)
+            .map(|u| u.name.clone())
+            .ok_or(Error::RowNotFound)?;
+        state.next_comment_id += 1;
+        let now = db_time(Utc::now());
+        let comment = CommentRow {
+            id: state.next_comment_id,
+            news_id,
+            user_id,
+            author,
+            parent_id,
+            root_id,
+            content: content.to_string(),
+            status: CommentStatus::Pending,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        };
+        state.comments.push(comment.clone());
+        Ok(comment)
+    }
+
+    async fn get_comment(&self, id: i32) -> Result<Option<CommentRow>, Error> {
+        Ok(self.state().comments.iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn update_comment_content(&self, id: i32, content: &str) -> Result<Option<CommentRow>, Error> {
+        let mut state = self.state();
+        let comment = state.comments.iter_mut().find(|c| c.id == id && c.deleted_at.is_none());
+        Ok(comment.map(|c| {
+            c.content = content.to_string();
+            c.status = CommentStatus::Pending;
+            c.updated_at = db_time(Utc::now());
+            c.clone()
+        }))
+    }
+
+    async fn delete_comment(&self, id: i32) -> Result<bool, Error> {
+        let mut state = self.state();
+        match state.comments.iter_mut().find(|c| c.id == id && c.deleted_at.is_none()) {
+            Some(comment) => {
+                comment.deleted_at = Some(db_time(Utc::now()));
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn set_comment_status(&self, id: i32, status: CommentStatus) -> Result<Option<CommentRow>, Error> {
+        let mut state = self.state();
+        let comment = state.comments.iter_mut().find(|c| c.id == id && c.deleted_at.is_none());
+        Ok(comment.map(|c| {
+            c.status = status;
+            c.clone()
+        }))
+    }
+
+    async fn list_comment_threads(&self, news_id: i32, limit: u32, offset: i64) -> Result<Vec<CommentRow>, Error> {
+        let state = self.state();
+        let roots: Vec<i32> = state
+            .comment_roots(news_id)
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit as usize)
+            .map(|c| c.id)
+            .collect();
+        let mut comments: Vec<CommentRow> = state
+            .comments
+            .iter()
+            .filter(|c| roots.contains(&c.root_id.unwrap_or(c.id)))
+            .cloned()
+            .collect();
+        comments.sort_by(comment_order);
+        Ok(comments)
+    }
+
+    async fn count_comment_threads(&self, news_id: i32) -> Result<i64, Error> {
+        Ok(self.state().comment_roots(news_id).len() as i64)
+    }
+
+    async fn list_comments_by_status(&self, status: CommentStatus, limit: u32, offset: i64) -> Result<Vec<CommentRow>, Error> {
+        let state = self.state();
+        let mut comments: Vec<&CommentRow> = state
+            .comments
+            .iter()
+            .filter(|c| c.status == status && c.deleted_at.is_none() && state.visible(c.news_id).is_some())
+            .collect();
+        comments.sort_by(|a, b| comment_order(a, b));
+        Ok(comments
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn count_comments_by_status(&self, status: CommentStatus) -> Result<i64, Error> {
+        let state = self.state();
+        Ok(state
+            .comments
+            .iter()
+            .filter(|c| c.status == status && c.deleted_at.is_none() && state.visible(c.news_id).is_some())
+            .count() as i64)
+    }
+
+    async fn set_reaction(&self, user_id: i32, news_id: i32, reaction: ReactionKind) -> Result<(), Error> {
+        let mut state = self.state();
+        let now = db_time(Utc::now());
+        match state.reactions.iter_mut().find(|r| r.user_id == user_id && r.news_id == news_id) {
+            Some(existing) if existing.reaction != reaction => {
+                existing.reaction = reaction;
+                existing.created_at = now;
+            }
+            Some(_) => {}
+            None => state.reactions.push(Reaction { user_id, news_id, reaction, created_at: now }),
+        }
+        Ok(())
+    }
+
+    async fn remove_reaction(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        let mut state = self.state();
+        let before = state.reactions.len();
+        state.reactions.retain(|r| !(r.user_id == user_id && r.news_id == news_id));
+        Ok(state.reactions.len() < before)
+    }
+
+    async fn reaction_counts(&self, news_ids: &[i32]) -> Result<Vec<ReactionCount>, Error> {
+        let mut counts: Vec<ReactionCount> = Vec::new();
+        for r in self.state().reactions.iter().filter(|r| news_ids.contains(&r.news_id)) {
+            match counts.iter_mut().find(|c| c.news_id == r.news_id && c.reaction == r.reaction) {
+                Some(count) => count.count += 1,
+                None => counts.push(ReactionCount { news_id: r.news_id, reaction: r.reaction, count: 1 }),
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn user_reactions(&self, user_id: i32, news_ids: &[i32]) -> Result<Vec<UserReaction>, Error> {
+        Ok(self
+            .state()
+            .reactions
+            .iter()
+            .filter(|r| r.user_id == user_id && news_ids.contains(&r.news_id))
+            .map(|r| UserReaction { news_id: r.news_id, reaction: r.reaction })
+            .collect())
+    }
+
+    async fn most_liked_news(
+        &self,
+        since: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<LikedNewsRow>, Error> {
+        let state = self.state();
+        let mut liked: Vec<(&NewsModel, i64)> = Vec::new();
+        for r in state.reactions.iter().filter(|r| r.reaction == ReactionKind::Like && r.created_at >= since) {
+            let Some(row) = state.visible(r.news_id) else { continue };
+            if category.is_some_and(|c| row.news.news_type != c) {
+                continue;
+            }
+            match liked.iter_mut().find(|(news, _)| news.id == r.news_id) {
+                Some((_, likes)) => *likes += 1,
+                None => liked.push((&row.news, 1)),
+            }
+        }
+        liked.sort_by(|(a, la), (b, lb)| {
+            a.news_type
+                .cmp(&b.news_type)
+                .then(lb.cmp(la))
+                .then(b.published_at.cmp(&a.published_at))
+                .then(b.id.cmp(&a.id))
+        });
+
+        let mut rows: Vec<LikedNewsRow> = Vec::new();
+        for (news, likes) in liked {
+            if rows.iter().filter(|r| r.news_type == news.news_type).count() >= limit as usize {
+                continue;
+            }
+            rows.push(LikedNewsRow {
+                id: news.id,
+                news_type: news.news_type.clone(),
+                href: news.href.clone(),
+                title: news.title.clone(),
+                published_at: news.published_at,
+                ingested_at: news.ingested_at,
+                content: news.content.clone(),
+                likes,
+            });
+        }
+        Ok(rows)
+    }
+
+    async fn trending_news(
+        &self,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<TrendingNewsRow>, Error> {
+        let state = self.state();
+        let since_hour = since.duration_trunc(Duration::hours(1)).expect("hour fits in a timestamp");
+        // (news_id, 浏览量, 点赞数)
+        let mut activity: Vec<(i32, i64, i64)> = Vec::new();
+        let mut add = |news_id: i32, views: i64, likes: i64| {
+            match activity.iter_mut().find(|(id, _, _)| *id == news_id) {
+                Some((_, v, l)) => {
+                    *v += views;
+                    *l += likes;
+                }
+                None => activity.push((news_id, views, likes)),
+            }
+        };
+        for bucket in state.views.iter().filter(|b| b.hour >= since_hour) {
+            add(bucket.news_id, bucket.views, 0);
+        }
+        for r in state.reactions.iter().filter(|r| r.reaction == ReactionKind::Like && r.created_at >= since) {
+            add(r.news_id, 0, 1);
+        }
+
+        let mut rows: Vec<TrendingNewsRow> = activity
+            .into_iter()
+            .filter_map(|(news_id, views, likes)| {
+                let news = &state.visible(news_id)?.news;
+                if category.is_some_and(|c| news.news_type != c) {
+                    return None;
+                }
+                let age_hours = (now - news.published_at).num_milliseconds() as f64 / 3_600_000.0;
+                Some(TrendingNewsRow {
+                    id: news.id,
+                    news_type: news.news_type.clone(),
+                    href: news.href.clone(),
+                    title: news.title.clone(),
+                    published_at: news.published_at,
+                    ingested_at: news.ingested_at,
+                    content: news.content.clone(),
+                    views,
+                    likes,
+                    score: hotness(views, likes, age_hours),
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.published_at.cmp(&a.published_at))
+                .then(b.id.cmp(&a.id))
+        });
+        rows.truncate(limit as usize);
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl UserStore for MemoryStore {
+    async fn create_user(&self, name: &str, email: &str, password: &str) -> Result<UserModel, Error> {
+        let mut state = self.state();
+        if state.users.iter().any(|u| u.email == email) {
+            return Err(unique_violation("users_email_key"));
+        }
+        state.next_user_id += 1;
+        let user = UserModel {
+            id: state.next_user_id,
+            name: name.to_string(),
+            email: email.to_string(),
+            password: password.to_string(),
+            role: UserRole::Reader,
+        };
+        state.users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> Result<UserModel, Error> {
+        self.state().users.iter().find(|u| u.email == email).cloned().ok_or(Error::RowNotFound)
+    }
+
+    async fn get_user_by_id(&self, user_id: i32) -> Result<UserModel, Error> {
+        self.state().users.iter().find(|u| u.id == user_id).cloned().ok_or(Error::RowNotFound)
+    }
+
+    async fn update_password(&self, user_id: i32, password: &str) -> Result<(), Error> {
+        if let Some(user) = self.state().users.iter_mut().find(|u| u.id == user_id) {
+            user.password = password.to_string();
+        }
+        Ok(())
+    }
+
+    async fn update_role(&self, user_id: i32, role: UserRole) -> Result<Option<UserModel>, Error> {
+        let mut state = self.state();
+        let user = state.users.iter_mut().find(|u| u.id == user_id);
+        Ok(user.map(|user| {
+            user.role = role;
+            user.clone()
+        }))
+    }
+
+    async fn get_subscriptions(&self, user_id: i32) -> Result<Vec<String>, Error> {
+        let mut categories: Vec<String> = self
+            .state()
+            .subscriptions
+            .iter()
+            .filter(|(id, _)| *id == user_id)
+            .map(|(_, category)| category.clone())
+            .collect();
+        categories.sort();
+        Ok(categories)
+    }
+
+    async fn set_subscriptions(&self, user_id: i32, categories: &[String]) -> Result<(), Error> {
+        let mut state = self.state();
+        state.subscriptions.retain(|(id, _)| *id != user_id);
+        for category in categories {
+            if !state.subscriptions.iter().any(|(id, c)| *id == user_id && c == category) {
+                state.subscriptions.push((user_id, category.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    async fn add_subscription(&self, user_id: i32, category: &str) -> Result<bool, Error> {
+        let mut state = self.state();
+        if state.subscriptions.iter().any(|(id, c)| *id == user_id && c == category) {
+            return Ok(false);
+        }
+        state.subscriptions.push((user_id, category.to_string()));
+        Ok(true)
+    }
+
+    async fn remove_subscription(&self, user_id: i32, category: &str) -> Result<bool, Error> {
+        let mut state = self.state();
+        let before = state.subscriptions.len();
+        state.subscriptions.retain(|(id, c)| !(*id == user_id && c == category));
+        Ok(state.subscriptions.len() < before)
+    }
+}
+
+// 在 db/memory.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_unique_constraints() {
+        let store = MemoryStore::new();
+        store.create_news("tech", "https://a", "A", "Content", None).await.unwrap();
+        let err = store.create_news("tech", "https://a", "B", "Content", None).await.unwrap_err();
+        match err {
+            Error::Database(db) => {
+                assert!(db.is_unique_violation());
+                assert_eq!(db.constraint(), Some("news_href_key"));
+            }
+            other => panic!("expected unique violation, got {:?}", other),
+        }
+
+        store.create_user("a", "a@example.com", "hash").await.unwrap();
+        assert!(store.create_user("b", "a@example.com", "hash").await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_soft_delete_and_upsert() {
+        let store = MemoryStore::new();
+        let news = store.create_news("tech", "https://a", "A", "Content", None).await.unwrap();
+        assert!(matches!(
+            store.upsert_news("tech", "https://a", "A", "Content", None).await.unwrap(),
+            UpsertOutcome::Unchanged
+        ));
+        assert!(matches!(
+            store.upsert_news("tech", "https://a", "A2", "Content", None).await.unwrap(),
+            UpsertOutcome::Updated(_)
+        ));
+
+        assert!(store.soft_delete_news(news.id).await.unwrap());
+        assert!(store.get_news_by_id(news.id).await.unwrap().is_none());
+        assert_eq!(store.get_news_count(&NewsFilter::default()).await.unwrap(), 0);
+        assert_eq!(store.restore_news(news.id).await.unwrap().unwrap().title, "A2");
+    }
+
+    #[actix_rt::test]
+    async fn test_search_ranks_title_hits_first() {
+        let store = MemoryStore::new();
+        let body_hit = store.create_news("tech", "https://a", "货轮太平洋起火", "载三千辆汽车", None).await.unwrap();
+        let title_hit = store.create_news("tech", "https://b", "汽车板块上涨", "股市收盘", None).await.unwrap();
+
+        let hits = store.search_news(&tokenize("汽车"), None, 10, 0).await.unwrap();
+        assert_eq!(hits.iter().map(|h| h.id).collect::<Vec<_>>(), vec![title_hit.id, body_hit.id]);
+        assert_eq!(store.search_news_count(&tokenize("太平洋 汽车"), None).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,104 @@
+use sqlx::migrate::{AppliedMigration, Migrate, MigrateError, Migrator};
+use sqlx::PgPool;
+use thiserror::Error;
+
+// 编译进二进制的迁移脚本（项目根目录 migrations/）
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("Migration error: {0}")]
+    Migrate(#[from] MigrateError),
+    #[error("Migration {0} was interrupted, the database needs manual repair")]
+    Dirty(i64),
+    #[error("Database has migration {0} which this build does not know, refusing to start with an older binary")]
+    Unknown(i64),
+    #[error("Migration {0} in the database differs from this build")]
+    Modified(i64),
+    #[error("Database schema is behind, pending migrations: {0:?} (run with --migrate)")]
+    Pending(Vec<i64>),
+}
+
+// 应用所有未执行的迁移；sqlx 在 Postgres 上使用咨询锁，多实例同时启动也只会执行一次
+pub async fn run_migrations(pool: &PgPool) -> Result<(), SchemaError> {
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
+
+// 校验数据库的迁移记录与本次构建完全一致，不一致时拒绝启动
+pub async fn check_schema(pool: &PgPool) -> Result<(), SchemaError> {
+    let mut conn = pool.acquire().await.map_err(MigrateError::from)?;
+    conn.ensure_migrations_table().await?;
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(SchemaError::Dirty(version));
+    }
+    let applied = conn.list_applied_migrations().await?;
+    compare_migrations(&MIGRATOR, &applied)
+}
+
+// 对比已执行的迁移与内置迁移：版本未知、内容被修改、或有未执行的迁移都视为不匹配
+fn compare_migrations(migrator: &Migrator, applied: &[AppliedMigration]) -> Result<(), SchemaError> {
+    for migration in applied {
+        match migrator.iter().find(|m| m.version == migration.version) {
+            None => return Err(SchemaError::Unknown(migration.version)),
+            Some(m) if m.checksum != migration.checksum => return Err(SchemaError::Modified(migration.version)),
+            Some(_) => {}
+        }
+    }
+
+    let pending: Vec<i64> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .filter(|version| !applied.iter().any(|a| a.version == *version))
+        .collect();
+    if pending.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::Pending(pending))
+    }
+}
+
+// 在 db/migrate.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixture::TestDb;
+
+    fn applied(versions: &[i64]) -> Vec<AppliedMigration> {
+        versions
+            .iter()
+            .map(|v| AppliedMigration {
+                version: *v,
+                checksum: MIGRATOR.iter().find(|m| m.version == *v).map(|m| m.checksum.clone()).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_migrations() {
+        let all: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        assert!(compare_migrations(&MIGRATOR, &applied(&all)).is_ok());
+
+        assert!(matches!(
+            compare_migrations(&MIGRATOR, &applied(&all[..1])),
+            Err(SchemaError::Pending(pending)) if pending == all[1..]
+        ));
+
+        let mut newer = applied(&all);
+        newer.push(AppliedMigration { version: 9999, checksum: Default::default() });
+        assert!(matches!(compare_migrations(&MIGRATOR, &newer), Err(SchemaError::Unknown(9999))));
+
+        let mut modified = applied(&all);
+        modified[0].checksum = vec![0u8; 4].into();
+        assert!(matches!(compare_migrations(&MIGRATOR, &modified), Err(SchemaError::Modified(_))));
+    }
+
+    #[actix_rt::test]
+    async fn test_migrations_are_idempotent() {
+        // 夹具已在全新的 schema 上执行过一次迁移
+        let db = TestDb::new().await;
+        run_migrations(&db.pool).await.unwrap();
+        check_schema(&db.pool).await.unwrap();
+    }
+}
@@ -1,7 +1,16 @@
 
 pub mod pool;
+pub mod migrate;
 pub mod models;
 pub mod repo;
+pub mod queries;
+pub mod tokenizer;
+pub mod store;
+pub mod memory;
+#[cfg(test)]
+pub mod fixture;
 
 // 统一导出常用类型
-pub use repo::{NewsRepo, UsersRepo};
\ No newline at end of file
+pub use repo::{NewsRepo, UsersRepo};
+pub use store::{NewsStore, UserStore};
+pub use memory::MemoryStore;
\ No newline at end of file
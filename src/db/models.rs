@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 
 // 新闻模型（严格匹配数据库表结构）
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -9,17 +9,272 @@ pub struct NewsModel {
     pub news_type: String,        // 数据库字段为 NOT NULL
     pub href: String,             // 数据库字段为 NOT NULL
     pub title: String,            // 数据库字段为 NOT NULL
-    pub datetime: NaiveDateTime,  // 数据库字段为 NOT NULL
+    pub published_at: DateTime<Utc>, // 原始发布时间，TIMESTAMPTZ NOT NULL
+    pub ingested_at: DateTime<Utc>,  // 入库时间，TIMESTAMPTZ NOT NULL
     pub content: String,          // 数据库字段为 NOT NULL
 }
 
+// 排序和过滤所依据的时间字段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeField {
+    #[default]
+    Published,
+    Ingested,
+}
+
+impl TimeField {
+    // 对应的数据库列名
+    pub fn column(&self) -> &'static str {
+        match self {
+            TimeField::Published => "published_at",
+            TimeField::Ingested => "ingested_at",
+        }
+    }
+}
+
+// 新闻列表排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NewsSort {
+    #[default]
+    Newest,
+    Oldest,
+    Title,
+    Popular, // 按浏览量
+}
+
+// 新闻列表过滤条件，各字段为空时不过滤；时间范围为 [from, to)
+#[derive(Debug, Clone, Default)]
+pub struct NewsFilter {
+    pub categories: Vec<String>,         // 只包含这些分类
+    pub exclude_categories: Vec<String>, // 排除这些分类
+    pub exclude_ids: Vec<i32>,           // 排除这些新闻
+    pub unread_by: Option<i32>,          // 排除该用户已读的新闻
+    pub time_field: TimeField,           // from/to 与 newest/oldest 排序依据的时间
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+// 游标分页的位置：排序所用时间列的值与 id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewsCursor {
+    pub time: DateTime<Utc>,
+    pub id: i32,
+}
+
+// 游标方向：After 取游标之后的一页（下一页），Before 取游标之前的一页（上一页）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyset {
+    After(NewsCursor),
+    Before(NewsCursor),
+}
+
+// 按 href 写入新闻的结果
+#[derive(Debug, Clone)]
+pub enum UpsertOutcome {
+    Inserted(NewsModel),
+    Updated(NewsModel),
+    Unchanged, // 已存在且标题、正文、时间均未变化
+}
+
+// 全文检索结果行：新闻字段加相关度得分
+#[derive(Debug, Clone, FromRow)]
+pub struct NewsSearchRow {
+    pub id: i32,
+    pub news_type: String,
+    pub href: String,
+    pub title: String,
+    pub published_at: DateTime<Utc>,
+    pub ingested_at: DateTime<Utc>,
+    pub content: String,
+    pub rank: f32,
+}
+
+// 阅读记录行：新闻字段加最近阅读时间和阅读次数
+#[derive(Debug, Clone, FromRow)]
+pub struct HistoryRow {
+    pub id: i32,
+    pub news_type: String,
+    pub href: String,
+    pub title: String,
+    pub published_at: DateTime<Utc>,
+    pub ingested_at: DateTime<Utc>,
+    pub content: String,
+    pub last_read_at: DateTime<Utc>,
+    pub read_count: i32,
+}
+
+// 新闻表态种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum ReactionKind {
+    Like,
+    Love,
+    Haha,
+    Wow,
+    Sad,
+    Angry,
+}
+
+impl ReactionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactionKind::Like => "like",
+            ReactionKind::Love => "love",
+            ReactionKind::Haha => "haha",
+            ReactionKind::Wow => "wow",
+            ReactionKind::Sad => "sad",
+            ReactionKind::Angry => "angry",
+        }
+    }
+}
+
+// 某篇新闻某种表态的数量
+#[derive(Debug, Clone, FromRow)]
+pub struct ReactionCount {
+    pub news_id: i32,
+    pub reaction: ReactionKind,
+    pub count: i64,
+}
+
+// 热度计算：一次点赞折合的浏览量与时间衰减指数，
+// score = (浏览量 + LIKE_WEIGHT * 点赞数) / (发布后小时数 + 2) ^ GRAVITY
+pub const HOTNESS_LIKE_WEIGHT: f64 = 5.0;
+pub const HOTNESS_GRAVITY: f64 = 1.5;
+
+// 与 queries::trending_news 中的 SQL 保持一致，供内存存储使用
+pub fn hotness(views: i64, likes: i64, age_hours: f64) -> f64 {
+    (views as f64 + HOTNESS_LIKE_WEIGHT * likes as f64) / (age_hours.max(0.0) + 2.0).powf(HOTNESS_GRAVITY)
+}
+
+// 热榜行：新闻字段加时间窗口内的浏览量、点赞数与热度
+#[derive(Debug, Clone, FromRow)]
+pub struct TrendingNewsRow {
+    pub id: i32,
+    pub news_type: String,
+    pub href: String,
+    pub title: String,
+    pub published_at: DateTime<Utc>,
+    pub ingested_at: DateTime<Utc>,
+    pub content: String,
+    pub views: i64,
+    pub likes: i64,
+    pub score: f64,
+}
+
+// 用户对某篇新闻的表态
+#[derive(Debug, Clone, FromRow)]
+pub struct UserReaction {
+    pub news_id: i32,
+    pub reaction: ReactionKind,
+}
+
+// 点赞排行行：新闻字段加统计时间窗口内的点赞数
+#[derive(Debug, Clone, FromRow)]
+pub struct LikedNewsRow {
+    pub id: i32,
+    pub news_type: String,
+    pub href: String,
+    pub title: String,
+    pub published_at: DateTime<Utc>,
+    pub ingested_at: DateTime<Utc>,
+    pub content: String,
+    pub likes: i64,
+}
+
+// 评论审核状态：pending 待审核（公开可见），approved 已审核，hidden 被编辑隐藏
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum CommentStatus {
+    Pending,
+    Approved,
+    Hidden,
+}
+
+impl CommentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentStatus::Pending => "pending",
+            CommentStatus::Approved => "approved",
+            CommentStatus::Hidden => "hidden",
+        }
+    }
+}
+
+// 评论（含作者名）；已删除的评论 deleted_at 不为空
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct CommentRow {
+    pub id: i32,
+    pub news_id: i32,
+    pub user_id: i32,
+    pub author: String,
+    pub parent_id: Option<i32>,
+    #[serde(skip_serializing)]
+    pub root_id: Option<i32>, // 所在讨论串的顶层评论，顶层评论为 None
+    pub content: String,
+    pub status: CommentStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl CommentRow {
+    // 未删除且未被隐藏的评论对所有人可见
+    pub fn is_visible(&self) -> bool {
+        self.deleted_at.is_none() && self.status != CommentStatus::Hidden
+    }
+}
+
+// 用户角色，按权限从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum UserRole {
+    Reader,
+    Editor,
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Reader => "reader",
+            UserRole::Editor => "editor",
+            UserRole::Admin => "admin",
+        }
+    }
+}
+
 // 用户模型（严格匹配数据库表结构）
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct UserModel {
     pub id: i32,
     pub name: String,             // 数据库字段为 NOT NULL
     pub email: String,            // 数据库字段为 NOT NULL
+    #[serde(skip_serializing)]   // Argon2 哈希，不随响应返回
     pub password: String,         // 数据库字段为 NOT NULL
+    pub role: UserRole,           // 数据库字段为 NOT NULL，默认 reader
+}
+
+// 服务端会话（session.store = "postgres"），用于列出和撤销登录会话
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SessionModel {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub sid: Option<String>, // 登录时写入会话的随机标识，用于识别当前会话
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// 修改用户角色请求体
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserRoleUpdate {
+    pub role: UserRole,
 }
 
 // 用户注册请求体
@@ -41,17 +296,17 @@ pub struct UserLogin {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Local;
 
     #[test]
     fn test_news_model_serialization() {
-        let datetime = Local::now().naive_utc();
+        let now = Utc::now();
         let news = NewsModel {
             id: 1,
             news_type: "tech".into(),
             href: "https://example.com".into(),
             title: "Test".into(),
-            datetime,
+            published_at: now,
+            ingested_at: now,
             content: "Content".into(),
         };
 
@@ -62,5 +317,16 @@ mod tests {
         // 测试反序列化
         let deserialized: NewsModel = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized.title, "Test");
+        assert_eq!(deserialized.published_at, now);
+    }
+
+    #[test]
+    fn test_user_role_order_and_serialization() {
+        assert!(UserRole::Reader < UserRole::Editor);
+        assert!(UserRole::Editor < UserRole::Admin);
+
+        assert_eq!(serde_json::to_string(&UserRole::Editor).unwrap(), "\"editor\"");
+        let update: UserRoleUpdate = serde_json::from_str(r#"{"role": "admin"}"#).unwrap();
+        assert_eq!(update.role, UserRole::Admin);
     }
 }
\ No newline at end of file
@@ -0,0 +1,1241 @@
+use crate::db::models::{
+    CommentRow, CommentStatus, HistoryRow, Keyset, LikedNewsRow, NewsFilter, NewsModel, NewsSearchRow, NewsSort,
+    ReactionCount, ReactionKind, SessionModel, TimeField, TrendingNewsRow, UpsertOutcome, UserModel, UserReaction, UserRole,
+    HOTNESS_GRAVITY, HOTNESS_LIKE_WEIGHT,
+};
+use crate::db::tokenizer::{search_query, search_vector};
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgConnection, PgExecutor, Postgres, QueryBuilder};
+
+// 所有 SQL 都写成对 Executor 泛型的函数：既可以传 &PgPool，也可以传 &mut *tx 在事务中执行。
+// repo::NewsRepo / repo::UsersRepo 只是在连接池上调用这些函数。
+// 需要执行多条语句的函数接收 &mut PgConnection（连接池连接或事务均可）。
+
+// ---------- 新闻 ----------
+
+// 创建新闻；未提供发布时间时以入库时间作为发布时间
+pub async fn create_news<'e, E: PgExecutor<'e>>(
+    executor: E,
+    news_type: &str,
+    href: &str,
+    title: &str,
+    content: &str,
+    published_at: Option<DateTime<Utc>>,
+) -> Result<NewsModel, Error> {
+    sqlx::query_as!(
+        NewsModel,
+        r#"
+        INSERT INTO news (
+            news_type, href, title, published_at, content, search_vector
+        ) VALUES ($1, $2, $3, COALESCE($4, now()), $5, $6::text::tsvector)
+        RETURNING id, news_type, href, title, published_at, ingested_at, content
+        "#,
+        news_type,
+        href,
+        title,
+        published_at,
+        content,
+        search_vector(title, content)
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// 按 href 幂等写入新闻：不存在则插入；已存在且标题、正文或发布时间有变化则更新，否则不做修改。
+// published_at 为 None 时，新记录使用入库时间，已有记录保留原时间；分类和入库时间不随重复采集改变。
+pub async fn upsert_news<'e, E: PgExecutor<'e>>(
+    executor: E,
+    news_type: &str,
+    href: &str,
+    title: &str,
+    content: &str,
+    published_at: Option<DateTime<Utc>>,
+) -> Result<UpsertOutcome, Error> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO news (
+            news_type, href, title, published_at, content, search_vector
+        ) VALUES ($1, $2, $3, COALESCE($4::timestamptz, now()), $5, $6::text::tsvector)
+        ON CONFLICT (href) DO UPDATE SET
+            title = EXCLUDED.title,
+            content = EXCLUDED.content,
+            published_at = COALESCE($4, news.published_at),
+            search_vector = EXCLUDED.search_vector
+        WHERE (news.title, news.content, news.published_at)
+            IS DISTINCT FROM (EXCLUDED.title, EXCLUDED.content, COALESCE($4, news.published_at))
+        RETURNING id, news_type, href, title, published_at, ingested_at, content, (xmax = 0) AS "inserted!"
+        "#,
+        news_type,
+        href,
+        title,
+        published_at,
+        content,
+        search_vector(title, content)
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(match row {
+        None => UpsertOutcome::Unchanged,
+        Some(row) => {
+            let news = NewsModel {
+                id: row.id,
+                news_type: row.news_type,
+                href: row.href,
+                title: row.title,
+                published_at: row.published_at,
+                ingested_at: row.ingested_at,
+                content: row.content,
+            };
+            if row.inserted {
+                UpsertOutcome::Inserted(news)
+            } else {
+                UpsertOutcome::Updated(news)
+            }
+        }
+    })
+}
+
+// 根据 ID 查询单条新闻，不存在时返回 None
+pub async fn get_news_by_id<'e, E: PgExecutor<'e>>(executor: E, id: i32) -> Result<Option<NewsModel>, Error> {
+    sqlx::query_as!(
+        NewsModel,
+        r#"
+        SELECT id, news_type, href, title, published_at, ingested_at, content
+        FROM news
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 相关新闻：同分类下发布时间最接近的若干条（排除自身）
+pub async fn get_related_news<'e, E: PgExecutor<'e>>(
+    executor: E,
+    news: &NewsModel,
+    limit: u32,
+) -> Result<Vec<NewsModel>, Error> {
+    sqlx::query_as!(
+        NewsModel,
+        r#"
+        SELECT id, news_type, href, title, published_at, ingested_at, content
+        FROM news
+        WHERE news_type = $1 AND id <> $2 AND deleted_at IS NULL
+        ORDER BY ABS(EXTRACT(EPOCH FROM (published_at - $3))), id DESC
+        LIMIT $4
+        "#,
+        news.news_type,
+        news.id,
+        news.published_at,
+        limit as i64
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 按新闻当前的标题和正文重建检索向量（标题权重 A，正文权重 B）
+pub async fn refresh_search_vector<'e, E: PgExecutor<'e>>(executor: E, news: &NewsModel) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE news SET search_vector = $2::text::tsvector WHERE id = $1",
+        news.id,
+        search_vector(&news.title, &news.content)
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 更新新闻，None 字段保持原值；已删除或不存在时返回 None。
+// 更新后还要重建检索向量，调用方应在事务中执行以保证两者一致
+pub async fn update_news(
+    conn: &mut PgConnection,
+    id: i32,
+    news_type: Option<&str>,
+    href: Option<&str>,
+    title: Option<&str>,
+    content: Option<&str>,
+    published_at: Option<DateTime<Utc>>,
+) -> Result<Option<NewsModel>, Error> {
+    let news = sqlx::query_as!(
+        NewsModel,
+        r#"
+        UPDATE news SET
+            news_type = COALESCE($2, news_type),
+            href = COALESCE($3, href),
+            title = COALESCE($4, title),
+            content = COALESCE($5, content),
+            published_at = COALESCE($6, published_at)
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING id, news_type, href, title, published_at, ingested_at, content
+        "#,
+        id,
+        news_type,
+        href,
+        title,
+        content,
+        published_at
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    // 标题或正文可能已变化，按更新后的内容重建检索向量
+    if let Some(news) = &news {
+        refresh_search_vector(&mut *conn, news).await?;
+    }
+    Ok(news)
+}
+
+// 为尚未建立检索向量的新闻（如 CSV 导入的数据）补建索引，返回处理条数
+pub async fn reindex_missing_search_vectors(conn: &mut PgConnection, batch_size: u32) -> Result<u64, Error> {
+    let mut total = 0;
+    loop {
+        let batch = sqlx::query_as!(
+            NewsModel,
+            r#"
+            SELECT id, news_type, href, title, published_at, ingested_at, content
+            FROM news
+            WHERE search_vector IS NULL
+            ORDER BY id
+            LIMIT $1
+            "#,
+            batch_size as i64
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if batch.is_empty() {
+            return Ok(total);
+        }
+        for news in &batch {
+            refresh_search_vector(&mut *conn, news).await?;
+        }
+        total += batch.len() as u64;
+    }
+}
+
+// 全文检索：检索词由 tokenizer::search_query 拼成 tsquery，结果按相关度排序
+pub async fn search_news<'e, E: PgExecutor<'e>>(
+    executor: E,
+    terms: &[String],
+    category: Option<&str>,
+    limit: u32,
+    offset: i64,
+) -> Result<Vec<NewsSearchRow>, Error> {
+    let Some(query) = search_query(terms) else {
+        return Ok(Vec::new());
+    };
+    sqlx::query_as!(
+        NewsSearchRow,
+        r#"
+        SELECT id, news_type, href, title, published_at, ingested_at, content,
+               ts_rank(search_vector, $1::text::tsquery) AS "rank!"
+        FROM news
+        WHERE search_vector @@ $1::text::tsquery
+          AND ($2::text IS NULL OR news_type = $2)
+          AND deleted_at IS NULL
+        ORDER BY 8 DESC, published_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        query,
+        category,
+        limit as i64,
+        offset
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 全文检索命中总数
+pub async fn search_news_count<'e, E: PgExecutor<'e>>(
+    executor: E,
+    terms: &[String],
+    category: Option<&str>,
+) -> Result<i64, Error> {
+    let Some(query) = search_query(terms) else {
+        return Ok(0);
+    };
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*)
+        FROM news
+        WHERE search_vector @@ $1::text::tsquery
+          AND ($2::text IS NULL OR news_type = $2)
+          AND deleted_at IS NULL
+        "#,
+        query,
+        category
+    )
+    .fetch_one(executor)
+    .await?
+    .unwrap_or(0);
+    Ok(count)
+}
+
+// 软删除新闻，返回是否有记录被删除
+pub async fn soft_delete_news<'e, E: PgExecutor<'e>>(executor: E, id: i32) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "UPDATE news SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 恢复已软删除的新闻；未删除或不存在时返回 None
+pub async fn restore_news<'e, E: PgExecutor<'e>>(executor: E, id: i32) -> Result<Option<NewsModel>, Error> {
+    sqlx::query_as!(
+        NewsModel,
+        r#"
+        UPDATE news SET deleted_at = NULL
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        RETURNING id, news_type, href, title, published_at, ingested_at, content
+        "#,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 按过滤条件、排序方式分页查询新闻
+pub async fn query_news<'e, E: PgExecutor<'e>>(
+    executor: E,
+    filter: &NewsFilter,
+    sort: NewsSort,
+    limit: u32,
+    offset: i64,
+) -> Result<Vec<NewsModel>, Error> {
+    let mut qb = QueryBuilder::new(
+        "SELECT id, news_type, href, title, published_at, ingested_at, content FROM news",
+    );
+    push_news_filter(&mut qb, filter);
+    push_news_order(&mut qb, sort, filter.time_field);
+    qb.push(" LIMIT ").push_bind(limit as i64);
+    qb.push(" OFFSET ").push_bind(offset);
+
+    qb.build_query_as::<NewsModel>().fetch_all(executor).await
+}
+
+// 导出全部符合条件的新闻，按时间正序；返回的查询由调用方逐行读取（fetch），不一次性载入内存
+pub fn export_news_query(filter: &NewsFilter) -> QueryBuilder<'static, Postgres> {
+    let mut qb = QueryBuilder::new(
+        "SELECT id, news_type, href, title, published_at, ingested_at, content FROM news",
+    );
+    push_news_filter(&mut qb, filter);
+    push_news_order(&mut qb, NewsSort::Oldest, filter.time_field);
+    qb
+}
+
+// 游标分页：按 (时间列, id) 定位，不使用 OFFSET，也不统计总数
+// descending 为 true 时按时间倒序（newest），否则正序（oldest）；
+// 返回结果始终按展示顺序排列，以及游标方向上是否还有更多数据
+pub async fn query_news_keyset<'e, E: PgExecutor<'e>>(
+    executor: E,
+    filter: &NewsFilter,
+    descending: bool,
+    keyset: Option<&Keyset>,
+    limit: u32,
+) -> Result<(Vec<NewsModel>, bool), Error> {
+    let column = filter.time_field.column();
+    // 向前翻页时反向扫描，取到结果后再反转回展示顺序
+    let backward = matches!(keyset, Some(Keyset::Before(_)));
+    let scan_desc = descending != backward;
+
+    let mut qb = QueryBuilder::new(
+        "SELECT id, news_type, href, title, published_at, ingested_at, content FROM news",
+    );
+    push_news_filter(&mut qb, filter);
+    if let Some(Keyset::After(cursor) | Keyset::Before(cursor)) = keyset {
+        let op = if scan_desc { "<" } else { ">" };
+        qb.push(format!(" AND ({}, id) {} (", column, op))
+            .push_bind(cursor.time)
+            .push(", ")
+            .push_bind(cursor.id)
+            .push(")");
+    }
+    let dir = if scan_desc { "DESC" } else { "ASC" };
+    qb.push(format!(" ORDER BY {} {}, id {}", column, dir, dir));
+    // 多取一条用于判断是否还有下一页
+    qb.push(" LIMIT ").push_bind(limit as i64 + 1);
+
+    let mut news = qb.build_query_as::<NewsModel>().fetch_all(executor).await?;
+    let has_more = news.len() > limit as usize;
+    news.truncate(limit as usize);
+    if backward {
+        news.reverse();
+    }
+    Ok((news, has_more))
+}
+
+// 按过滤条件统计新闻数量（与 query_news 使用同样的条件）
+pub async fn get_news_count<'e, E: PgExecutor<'e>>(executor: E, filter: &NewsFilter) -> Result<i64, Error> {
+    let mut qb = QueryBuilder::new("SELECT COUNT(*) FROM news");
+    push_news_filter(&mut qb, filter);
+    qb.build_query_scalar::<i64>().fetch_one(executor).await
+}
+
+// 浏览量 +1（详情页调用）：累计浏览量用于 popular 排序，按小时汇总的浏览量用于热榜
+pub async fn increment_view_count<'e, E: PgExecutor<'e>>(executor: E, id: i32) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        WITH viewed AS (
+            UPDATE news SET view_count = view_count + 1 WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id
+        )
+        INSERT INTO news_views (news_id, hour, views)
+        SELECT id, date_trunc('hour', now()), 1 FROM viewed
+        ON CONFLICT (news_id, hour) DO UPDATE SET views = news_views.views + 1
+        "#,
+        id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 拼接 WHERE 条件：未删除，再按 filter 中非空的字段逐个追加
+fn push_news_filter(qb: &mut QueryBuilder<'_, Postgres>, filter: &NewsFilter) {
+    let column = filter.time_field.column();
+    qb.push(" WHERE deleted_at IS NULL");
+    if !filter.categories.is_empty() {
+        qb.push(" AND news_type = ANY(").push_bind(filter.categories.clone()).push(")");
+    }
+    if !filter.exclude_categories.is_empty() {
+        qb.push(" AND news_type <> ALL(").push_bind(filter.exclude_categories.clone()).push(")");
+    }
+    if !filter.exclude_ids.is_empty() {
+        qb.push(" AND id <> ALL(").push_bind(filter.exclude_ids.clone()).push(")");
+    }
+    if let Some(user_id) = filter.unread_by {
+        qb.push(" AND NOT EXISTS (SELECT 1 FROM reading_history h WHERE h.news_id = news.id AND h.user_id = ")
+            .push_bind(user_id)
+            .push(")");
+    }
+    if let Some(from) = filter.from {
+        qb.push(format!(" AND {} >= ", column)).push_bind(from);
+    }
+    if let Some(to) = filter.to {
+        qb.push(format!(" AND {} < ", column)).push_bind(to);
+    }
+}
+
+// 拼接 ORDER BY；最后按 id 排序保证分页结果稳定
+fn push_news_order(qb: &mut QueryBuilder<'_, Postgres>, sort: NewsSort, time_field: TimeField) {
+    let column = time_field.column();
+    let order = match sort {
+        NewsSort::Newest => format!("{} DESC, id DESC", column),
+        NewsSort::Oldest => format!("{} ASC, id ASC", column),
+        NewsSort::Title => "title ASC, id ASC".to_string(),
+        NewsSort::Popular => format!("view_count DESC, {} DESC, id DESC", column),
+    };
+    qb.push(" ORDER BY ").push(order);
+}
+
+
+// ---------- 用户 ----------
+
+// 创建用户（强制非空字段）
+pub async fn create_user<'e, E: PgExecutor<'e>>(
+    executor: E,
+    name: &str,
+    email: &str,
+    password: &str,
+) -> Result<UserModel, Error> {
+    sqlx::query_as!(
+        UserModel,
+        r#"
+        INSERT INTO users (name, email, password)
+        VALUES ($1, $2, $3)
+        RETURNING id, name, email, password, role as "role: UserRole"
+        "#,
+        name,
+        email,
+        password
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// 根据邮箱查询用户
+pub async fn get_user_by_email<'e, E: PgExecutor<'e>>(executor: E, email: &str) -> Result<UserModel, Error> {
+    sqlx::query_as!(
+        UserModel,
+        r#"
+        SELECT id, name, email, password, role as "role: UserRole"
+        FROM users
+        WHERE email = $1
+        "#,
+        email
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// 根据用户ID查询用户信息
+pub async fn get_user_by_id<'e, E: PgExecutor<'e>>(executor: E, user_id: i32) -> Result<UserModel, Error> {
+    sqlx::query_as!(
+        UserModel,
+        r#"
+        SELECT id, name, email, password, role as "role: UserRole"
+        FROM users
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// 更新用户密码（用于登录时把旧的明文密码迁移为哈希）
+pub async fn update_password<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, password: &str) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE users SET password = $1 WHERE id = $2",
+        password,
+        user_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 修改用户角色，用户不存在时返回 None
+pub async fn update_role<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    role: UserRole,
+) -> Result<Option<UserModel>, Error> {
+    sqlx::query_as!(
+        UserModel,
+        r#"
+        UPDATE users SET role = $1
+        WHERE id = $2
+        RETURNING id, name, email, password, role as "role: UserRole"
+        "#,
+        role.as_str(),
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 用户订阅的分类，按名称（字节序）排序
+pub async fn get_subscriptions<'e, E: PgExecutor<'e>>(executor: E, user_id: i32) -> Result<Vec<String>, Error> {
+    sqlx::query_scalar!(
+        "SELECT category FROM category_subscriptions WHERE user_id = $1 ORDER BY category COLLATE \"C\"",
+        user_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 用 categories 替换用户的全部订阅；应在事务中调用
+pub async fn set_subscriptions(conn: &mut PgConnection, user_id: i32, categories: &[String]) -> Result<(), Error> {
+    sqlx::query!("DELETE FROM category_subscriptions WHERE user_id = $1", user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO category_subscriptions (user_id, category)
+        SELECT $1, category FROM unnest($2::text[]) AS category
+        ON CONFLICT (user_id, category) DO NOTHING
+        "#,
+        user_id,
+        categories
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// 返回是否新增
+pub async fn add_subscription<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, category: &str) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO category_subscriptions (user_id, category) VALUES ($1, $2)
+        ON CONFLICT (user_id, category) DO NOTHING
+        "#,
+        user_id,
+        category
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 返回是否有订阅被删除
+pub async fn remove_subscription<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, category: &str) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "DELETE FROM category_subscriptions WHERE user_id = $1 AND category = $2",
+        user_id,
+        category
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// ---------- 会话 ----------
+
+// state 为 actix-session 的会话状态（键到 JSON 字符串的映射）
+pub async fn insert_session<'e, E: PgExecutor<'e>>(
+    executor: E,
+    session_key: &str,
+    user_id: Option<i32>,
+    state: &serde_json::Value,
+    expires_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO sessions (session_key, user_id, state, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        session_key,
+        user_id,
+        state,
+        expires_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 读取未过期会话的状态
+pub async fn load_session<'e, E: PgExecutor<'e>>(executor: E, session_key: &str) -> Result<Option<serde_json::Value>, Error> {
+    sqlx::query_scalar!(
+        "SELECT state FROM sessions WHERE session_key = $1 AND expires_at > now()",
+        session_key
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 返回是否有会话被更新（会话已过期或已被撤销时为 false）
+pub async fn update_session<'e, E: PgExecutor<'e>>(
+    executor: E,
+    session_key: &str,
+    user_id: Option<i32>,
+    state: &serde_json::Value,
+    expires_at: DateTime<Utc>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE sessions SET user_id = $2, state = $3, expires_at = $4, updated_at = now()
+        WHERE session_key = $1 AND expires_at > now()
+        "#,
+        session_key,
+        user_id,
+        state,
+        expires_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn update_session_expiry<'e, E: PgExecutor<'e>>(
+    executor: E,
+    session_key: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE sessions SET expires_at = $2, updated_at = now() WHERE session_key = $1",
+        session_key,
+        expires_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_session<'e, E: PgExecutor<'e>>(executor: E, session_key: &str) -> Result<(), Error> {
+    sqlx::query!("DELETE FROM sessions WHERE session_key = $1", session_key)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+// 用户未过期的会话，最近活跃的在前
+pub async fn list_user_sessions<'e, E: PgExecutor<'e>>(executor: E, user_id: i32) -> Result<Vec<SessionModel>, Error> {
+    // 会话状态中的值是 JSON 编码后的字符串，sid 需要再解码一次
+    sqlx::query_as!(
+        SessionModel,
+        r#"
+        SELECT id, (state->>'sid')::jsonb #>> '{}' AS sid, created_at, updated_at, expires_at
+        FROM sessions
+        WHERE user_id = $1 AND expires_at > now()
+        ORDER BY updated_at DESC, id DESC
+        "#,
+        user_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 撤销用户的某个会话，返回是否有会话被删除
+pub async fn delete_user_session<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, id: i64) -> Result<bool, Error> {
+    let result = sqlx::query!("DELETE FROM sessions WHERE id = $1 AND user_id = $2", id, user_id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 撤销用户的全部会话；keep_sid 不为空时保留该会话（通常是当前会话），返回删除条数
+pub async fn delete_user_sessions<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    keep_sid: Option<&str>,
+) -> Result<u64, Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM sessions
+        WHERE user_id = $1
+          AND ($2::text IS NULL OR (state->>'sid')::jsonb #>> '{}' IS DISTINCT FROM $2)
+        "#,
+        user_id,
+        keep_sid
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// 清理已过期的会话，返回删除条数
+pub async fn delete_expired_sessions<'e, E: PgExecutor<'e>>(executor: E) -> Result<u64, Error> {
+    let result = sqlx::query!("DELETE FROM sessions WHERE expires_at <= now()")
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+
+// ---------- 收藏 ----------
+
+// 收藏未删除的新闻，返回是否新增（已收藏或新闻不存在时为 false）
+pub async fn add_bookmark<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, news_id: i32) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO bookmarks (user_id, news_id)
+        SELECT $1, id FROM news WHERE id = $2 AND deleted_at IS NULL
+        ON CONFLICT (user_id, news_id) DO NOTHING
+        "#,
+        user_id,
+        news_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 返回是否有收藏被删除
+pub async fn remove_bookmark<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, news_id: i32) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "DELETE FROM bookmarks WHERE user_id = $1 AND news_id = $2",
+        user_id,
+        news_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 用户收藏的未删除新闻，最近收藏的在前
+pub async fn list_bookmarks<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    limit: u32,
+    offset: i64,
+) -> Result<Vec<NewsModel>, Error> {
+    sqlx::query_as!(
+        NewsModel,
+        r#"
+        SELECT n.id, n.news_type, n.href, n.title, n.published_at, n.ingested_at, n.content
+        FROM bookmarks b
+        JOIN news n ON n.id = b.news_id
+        WHERE b.user_id = $1 AND n.deleted_at IS NULL
+        ORDER BY b.created_at DESC, b.news_id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        user_id,
+        limit as i64,
+        offset
+    )
+    .fetch_all(executor)
+    .await
+}
+
+pub async fn count_bookmarks<'e, E: PgExecutor<'e>>(executor: E, user_id: i32) -> Result<i64, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM bookmarks b
+        JOIN news n ON n.id = b.news_id
+        WHERE b.user_id = $1 AND n.deleted_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// news_ids 中被用户收藏的 ID，用于在列表中标记
+pub async fn bookmarked_ids<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    news_ids: &[i32],
+) -> Result<Vec<i32>, Error> {
+    sqlx::query_scalar!(
+        "SELECT news_id FROM bookmarks WHERE user_id = $1 AND news_id = ANY($2)",
+        user_id,
+        news_ids
+    )
+    .fetch_all(executor)
+    .await
+}
+
+
+// ---------- 阅读记录 ----------
+
+// 记录一次阅读：首次阅读时插入，之后更新最近阅读时间并累计次数
+pub async fn record_read<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, news_id: i32) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO reading_history (user_id, news_id)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id, news_id) DO UPDATE SET
+            last_read_at = now(),
+            read_count = reading_history.read_count + 1
+        "#,
+        user_id,
+        news_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 用户读过的未删除新闻，最近阅读的在前
+pub async fn list_history<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    limit: u32,
+    offset: i64,
+) -> Result<Vec<HistoryRow>, Error> {
+    sqlx::query_as!(
+        HistoryRow,
+        r#"
+        SELECT n.id, n.news_type, n.href, n.title, n.published_at, n.ingested_at, n.content,
+               h.last_read_at, h.read_count
+        FROM reading_history h
+        JOIN news n ON n.id = h.news_id
+        WHERE h.user_id = $1 AND n.deleted_at IS NULL
+        ORDER BY h.last_read_at DESC, h.news_id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        user_id,
+        limit as i64,
+        offset
+    )
+    .fetch_all(executor)
+    .await
+}
+
+pub async fn count_history<'e, E: PgExecutor<'e>>(executor: E, user_id: i32) -> Result<i64, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM reading_history h
+        JOIN news n ON n.id = h.news_id
+        WHERE h.user_id = $1 AND n.deleted_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// 返回是否有记录被删除
+pub async fn remove_history<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, news_id: i32) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "DELETE FROM reading_history WHERE user_id = $1 AND news_id = $2",
+        user_id,
+        news_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 清空用户的阅读记录，返回删除条数
+pub async fn clear_history<'e, E: PgExecutor<'e>>(executor: E, user_id: i32) -> Result<u64, Error> {
+    let result = sqlx::query!("DELETE FROM reading_history WHERE user_id = $1", user_id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+
+// ---------- 评论 ----------
+
+// 发表评论；回复时 parent_id 为被回复的评论，root_id 为所在讨论串的顶层评论
+pub async fn create_comment<'e, E: PgExecutor<'e>>(
+    executor: E,
+    news_id: i32,
+    user_id: i32,
+    parent_id: Option<i32>,
+    root_id: Option<i32>,
+    content: &str,
+) -> Result<CommentRow, Error> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"
+        WITH inserted AS (
+            INSERT INTO comments (news_id, user_id, parent_id, root_id, content)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+        )
+        SELECT c.id, c.news_id, c.user_id, u.name AS author, c.parent_id, c.root_id, c.content,
+               c.status AS "status: CommentStatus", c.created_at, c.updated_at, c.deleted_at
+        FROM inserted c
+        JOIN users u ON u.id = c.user_id
+        "#,
+        news_id,
+        user_id,
+        parent_id,
+        root_id,
+        content
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// 根据 ID 查询评论（包括已删除的）
+pub async fn get_comment<'e, E: PgExecutor<'e>>(executor: E, id: i32) -> Result<Option<CommentRow>, Error> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"
+        SELECT c.id, c.news_id, c.user_id, u.name AS author, c.parent_id, c.root_id, c.content,
+               c.status AS "status: CommentStatus", c.created_at, c.updated_at, c.deleted_at
+        FROM comments c
+        JOIN users u ON u.id = c.user_id
+        WHERE c.id = $1
+        "#,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 修改评论内容，修改后重新进入审核队列；已删除或不存在时返回 None
+pub async fn update_comment_content<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: i32,
+    content: &str,
+) -> Result<Option<CommentRow>, Error> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"
+        WITH updated AS (
+            UPDATE comments SET content = $2, status = 'pending', updated_at = now()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING *
+        )
+        SELECT c.id, c.news_id, c.user_id, u.name AS author, c.parent_id, c.root_id, c.content,
+               c.status AS "status: CommentStatus", c.created_at, c.updated_at, c.deleted_at
+        FROM updated c
+        JOIN users u ON u.id = c.user_id
+        "#,
+        id,
+        content
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 软删除评论，回复保留；返回是否有评论被删除
+pub async fn delete_comment<'e, E: PgExecutor<'e>>(executor: E, id: i32) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "UPDATE comments SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// 修改审核状态；已删除或不存在时返回 None
+pub async fn set_comment_status<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: i32,
+    status: CommentStatus,
+) -> Result<Option<CommentRow>, Error> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"
+        WITH updated AS (
+            UPDATE comments SET status = $2
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING *
+        )
+        SELECT c.id, c.news_id, c.user_id, u.name AS author, c.parent_id, c.root_id, c.content,
+               c.status AS "status: CommentStatus", c.created_at, c.updated_at, c.deleted_at
+        FROM updated c
+        JOIN users u ON u.id = c.user_id
+        "#,
+        id,
+        status.as_str()
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 按顶层评论分页（最新的在前）返回讨论串中的全部评论，包括已删除、已隐藏的，由调用方决定如何展示。
+// 只统计自身可见或有可见回复的顶层评论
+pub async fn list_comment_threads<'e, E: PgExecutor<'e>>(
+    executor: E,
+    news_id: i32,
+    limit: u32,
+    offset: i64,
+) -> Result<Vec<CommentRow>, Error> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"
+        WITH roots AS (
+            SELECT c.id FROM comments c
+            WHERE c.news_id = $1 AND c.root_id IS NULL
+              AND ((c.deleted_at IS NULL AND c.status <> 'hidden')
+                   OR EXISTS (SELECT 1 FROM comments r
+                              WHERE r.root_id = c.id AND r.deleted_at IS NULL AND r.status <> 'hidden'))
+            ORDER BY c.created_at DESC, c.id DESC
+            LIMIT $2 OFFSET $3
+        )
+        SELECT c.id, c.news_id, c.user_id, u.name AS author, c.parent_id, c.root_id, c.content,
+               c.status AS "status: CommentStatus", c.created_at, c.updated_at, c.deleted_at
+        FROM comments c
+        JOIN users u ON u.id = c.user_id
+        WHERE c.id IN (SELECT id FROM roots) OR c.root_id IN (SELECT id FROM roots)
+        ORDER BY c.created_at, c.id
+        "#,
+        news_id,
+        limit as i64,
+        offset
+    )
+    .fetch_all(executor)
+    .await
+}
+
+pub async fn count_comment_threads<'e, E: PgExecutor<'e>>(executor: E, news_id: i32) -> Result<i64, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM comments c
+        WHERE c.news_id = $1 AND c.root_id IS NULL
+          AND ((c.deleted_at IS NULL AND c.status <> 'hidden')
+               OR EXISTS (SELECT 1 FROM comments r
+                          WHERE r.root_id = c.id AND r.deleted_at IS NULL AND r.status <> 'hidden'))
+        "#,
+        news_id
+    )
+    .fetch_one(executor)
+    .await
+}
+
+// 审核队列：指定状态的未删除评论（所属新闻未删除），最早的在前
+pub async fn list_comments_by_status<'e, E: PgExecutor<'e>>(
+    executor: E,
+    status: CommentStatus,
+    limit: u32,
+    offset: i64,
+) -> Result<Vec<CommentRow>, Error> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"
+        SELECT c.id, c.news_id, c.user_id, u.name AS author, c.parent_id, c.root_id, c.content,
+               c.status AS "status: CommentStatus", c.created_at, c.updated_at, c.deleted_at
+        FROM comments c
+        JOIN users u ON u.id = c.user_id
+        JOIN news n ON n.id = c.news_id
+        WHERE c.status = $1 AND c.deleted_at IS NULL AND n.deleted_at IS NULL
+        ORDER BY c.created_at, c.id
+        LIMIT $2 OFFSET $3
+        "#,
+        status.as_str(),
+        limit as i64,
+        offset
+    )
+    .fetch_all(executor)
+    .await
+}
+
+pub async fn count_comments_by_status<'e, E: PgExecutor<'e>>(executor: E, status: CommentStatus) -> Result<i64, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM comments c
+        JOIN news n ON n.id = c.news_id
+        WHERE c.status = $1 AND c.deleted_at IS NULL AND n.deleted_at IS NULL
+        "#,
+        status.as_str()
+    )
+    .fetch_one(executor)
+    .await
+}
+
+
+// ---------- 表态 ----------
+
+// 设置用户对新闻的表态，已有表态时替换（种类相同则不做修改）
+pub async fn set_reaction<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    news_id: i32,
+    reaction: ReactionKind,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO reactions (user_id, news_id, reaction) VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, news_id) DO UPDATE SET reaction = EXCLUDED.reaction, created_at = now()
+        WHERE reactions.reaction <> EXCLUDED.reaction
+        "#,
+        user_id,
+        news_id,
+        reaction.as_str()
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 返回是否有表态被删除
+pub async fn remove_reaction<'e, E: PgExecutor<'e>>(executor: E, user_id: i32, news_id: i32) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        "DELETE FROM reactions WHERE user_id = $1 AND news_id = $2",
+        user_id,
+        news_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// news_ids 中各新闻的各表态数量（数量为 0 的不返回）
+pub async fn reaction_counts<'e, E: PgExecutor<'e>>(executor: E, news_ids: &[i32]) -> Result<Vec<ReactionCount>, Error> {
+    sqlx::query_as!(
+        ReactionCount,
+        r#"
+        SELECT news_id, reaction AS "reaction: ReactionKind", COUNT(*) AS "count!"
+        FROM reactions
+        WHERE news_id = ANY($1)
+        GROUP BY news_id, reaction
+        "#,
+        news_ids
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 用户对 news_ids 中新闻的表态
+pub async fn user_reactions<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i32,
+    news_ids: &[i32],
+) -> Result<Vec<UserReaction>, Error> {
+    sqlx::query_as!(
+        UserReaction,
+        r#"
+        SELECT news_id, reaction AS "reaction: ReactionKind"
+        FROM reactions
+        WHERE user_id = $1 AND news_id = ANY($2)
+        "#,
+        user_id,
+        news_ids
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// since 之后点赞最多的未删除新闻，每个分类取前 limit 条；按分类、点赞数排序
+pub async fn most_liked_news<'e, E: PgExecutor<'e>>(
+    executor: E,
+    since: DateTime<Utc>,
+    category: Option<&str>,
+    limit: u32,
+) -> Result<Vec<LikedNewsRow>, Error> {
+    sqlx::query_as!(
+        LikedNewsRow,
+        r#"
+        SELECT id AS "id!", news_type AS "news_type!", href AS "href!", title AS "title!",
+               published_at AS "published_at!", ingested_at AS "ingested_at!", content AS "content!",
+               likes AS "likes!"
+        FROM (
+            SELECT n.id, n.news_type, n.href, n.title, n.published_at, n.ingested_at, n.content,
+                   COUNT(*) AS likes,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY n.news_type ORDER BY COUNT(*) DESC, n.published_at DESC, n.id DESC
+                   ) AS position
+            FROM reactions r
+            JOIN news n ON n.id = r.news_id
+            WHERE r.reaction = 'like' AND r.created_at >= $1 AND n.deleted_at IS NULL
+              AND ($2::text IS NULL OR n.news_type = $2)
+            GROUP BY n.id
+        ) ranked
+        WHERE position <= $3
+        ORDER BY news_type, position
+        "#,
+        since,
+        category,
+        limit as i64
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 热榜：since 之后有浏览或点赞的新闻按热度排序（热度公式见 models::hotness）；
+// 浏览量按小时汇总，since 所在的小时整体计入
+pub async fn trending_news<'e, E: PgExecutor<'e>>(
+    executor: E,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+    category: Option<&str>,
+    limit: u32,
+) -> Result<Vec<TrendingNewsRow>, Error> {
+    sqlx::query_as!(
+        TrendingNewsRow,
+        r#"
+        WITH activity AS (
+            SELECT news_id, SUM(views)::bigint AS views, 0::bigint AS likes
+            FROM news_views
+            WHERE hour >= date_trunc('hour', $1::timestamptz)
+            GROUP BY news_id
+            UNION ALL
+            SELECT news_id, 0::bigint, COUNT(*)
+            FROM reactions
+            WHERE reaction = 'like' AND created_at >= $1
+            GROUP BY news_id
+        ), totals AS (
+            SELECT news_id, SUM(views)::bigint AS views, SUM(likes)::bigint AS likes
+            FROM activity
+            GROUP BY news_id
+        )
+        SELECT n.id AS "id!", n.news_type AS "news_type!", n.href AS "href!", n.title AS "title!",
+               n.published_at AS "published_at!", n.ingested_at AS "ingested_at!", n.content AS "content!",
+               t.views AS "views!", t.likes AS "likes!",
+               (t.views + $3::float8 * t.likes)
+                   / POWER(GREATEST(EXTRACT(EPOCH FROM ($2 - n.published_at))::float8 / 3600, 0) + 2, $4::float8)
+                   AS "score!"
+        FROM totals t
+        JOIN news n ON n.id = t.news_id
+        WHERE n.deleted_at IS NULL AND ($5::text IS NULL OR n.news_type = $5)
+        ORDER BY "score!" DESC, n.published_at DESC, n.id DESC
+        LIMIT $6
+        "#,
+        since,
+        now,
+        HOTNESS_LIKE_WEIGHT,
+        HOTNESS_GRAVITY,
+        category,
+        limit as i64
+    )
+    .fetch_all(executor)
+    .await
+}
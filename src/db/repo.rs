@@ -1,8 +1,16 @@
-use crate::db::models::{NewsModel, UserModel};
+use crate::db::models::{CommentRow, CommentStatus, HistoryRow, Keyset, LikedNewsRow, NewsFilter, NewsModel, NewsSearchRow, NewsSort, ReactionCount, ReactionKind, TrendingNewsRow, UpsertOutcome, UserModel, UserReaction, UserRole};
+use crate::db::queries;
+use crate::db::store::{NewsStore, UserStore};
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
 use sqlx::{PgPool, Error};
-use chrono::{Utc, NaiveDateTime};
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc;
 
-// 新闻仓库实现
+// 导出时在读取数据库与写出响应之间缓冲的行数；客户端读得慢时数据库读取随之暂停
+const EXPORT_BUFFER: usize = 64;
+
+// 新闻仓库实现：在连接池上执行 queries 中的 SQL；需要事务时直接调用 queries 并传入 &mut *tx
 #[derive(Clone)]  // 新增 Clone 派生
 pub struct NewsRepo {
     pool: PgPool,
@@ -13,136 +21,251 @@ impl NewsRepo {
         Self { pool }
     }
 
-    // 创建新闻（强制非空字段）
-    pub async fn create_news(
+    // 按新闻当前的标题和正文重建检索向量（标题权重 A，正文权重 B）
+    pub async fn refresh_search_vector(&self, news: &NewsModel) -> Result<(), Error> {
+        queries::refresh_search_vector(&self.pool, news).await
+    }
+}
+
+#[async_trait]
+impl NewsStore for NewsRepo {
+    async fn create_news(
         &self,
         news_type: &str,
         href: &str,
         title: &str,
         content: &str,
+        published_at: Option<DateTime<Utc>>,
     ) -> Result<NewsModel, Error> {
-        let created_at = Utc::now().naive_utc();
-        sqlx::query_as!(
-            NewsModel,
-            r#"
-            INSERT INTO news (
-                news_type, href, title, datetime, content
-            ) VALUES ($1, $2, $3, $4, $5)
-            RETURNING id, news_type, href, title, datetime, content
-            "#,
-            news_type,
-            href,
-            title,
-            created_at,
-            content
-        )
-        .fetch_one(&self.pool)
-        .await
+        queries::create_news(&self.pool, news_type, href, title, content, published_at).await
     }
 
+    async fn upsert_news(
+        &self,
+        news_type: &str,
+        href: &str,
+        title: &str,
+        content: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<UpsertOutcome, Error> {
+        queries::upsert_news(&self.pool, news_type, href, title, content, published_at).await
+    }
+
+    async fn get_news_by_id(&self, id: i32) -> Result<Option<NewsModel>, Error> {
+        queries::get_news_by_id(&self.pool, id).await
+    }
 
-    // 修改 get_paginated 方法的参数和 offset 计算
-    pub async fn get_paginated(
+    async fn get_related_news(&self, news: &NewsModel, limit: u32) -> Result<Vec<NewsModel>, Error> {
+        queries::get_related_news(&self.pool, news, limit).await
+    }
+
+    // 更新内容和重建检索向量放在同一个事务中
+    async fn update_news(
         &self,
-        page: u32,
-        page_size: u32,
-    ) -> Result<Vec<NewsModel>, Error> {
-        // 安全计算 offset（避免 u32 溢出，若 page 或 page_size 可能过大，建议用 i64 参数）
-        let offset = (page.saturating_sub(1) as i64) * (page_size as i64); // 转为 i64 避免溢出
-        sqlx::query_as!(
-            NewsModel,
-            r#"
-            SELECT
-                id,
-                news_type,
-                href,
-                title,
-                datetime,
-                content
-            FROM news
-            ORDER BY datetime DESC
-            LIMIT $1 OFFSET $2
-            "#,
-            page_size as i64, // LIMIT 接受 i64
-            offset // 已为 i64
-        )
-        .fetch_all(&self.pool)
-        .await
+        id: i32,
+        news_type: Option<&str>,
+        href: Option<&str>,
+        title: Option<&str>,
+        content: Option<&str>,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<NewsModel>, Error> {
+        let mut tx = self.pool.begin().await?;
+        let news = queries::update_news(&mut tx, id, news_type, href, title, content, published_at).await?;
+        tx.commit().await?;
+        Ok(news)
     }
-    
-    /* 
-    // 在 NewsRepo 实现中添加
-    pub async fn get_news_count(&self, category: Option<&str>) -> Result<i64, Error> {
-        match category {
-            Some(cat) => {
-                sqlx::query_scalar!(
-                    "SELECT COUNT(*) FROM news WHERE news_type = $1",
-                    cat
-                )
-                .fetch_one(&self.pool)
-                .await
-            }
-            None => {
-                sqlx::query_scalar!("SELECT COUNT(*) FROM news")
-                    .fetch_one(&self.pool)
-                    .await
-            }
-        }
+
+    async fn reindex_missing_search_vectors(&self, batch_size: u32) -> Result<u64, Error> {
+        let mut conn = self.pool.acquire().await?;
+        queries::reindex_missing_search_vectors(&mut conn, batch_size).await
+    }
+
+    async fn search_news(
+        &self,
+        terms: &[String],
+        category: Option<&str>,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Vec<NewsSearchRow>, Error> {
+        queries::search_news(&self.pool, terms, category, limit, offset).await
+    }
+
+    async fn search_news_count(&self, terms: &[String], category: Option<&str>) -> Result<i64, Error> {
+        queries::search_news_count(&self.pool, terms, category).await
+    }
+
+    async fn soft_delete_news(&self, id: i32) -> Result<bool, Error> {
+        queries::soft_delete_news(&self.pool, id).await
+    }
+
+    async fn restore_news(&self, id: i32) -> Result<Option<NewsModel>, Error> {
+        queries::restore_news(&self.pool, id).await
     }
-    */
-    ///*
-    // 修改 get_news_count 方法，处理 Option<i64>
-pub async fn get_news_count(&self, category: Option<&str>) -> Result<i64, Error> {
-    let count = match category {
-        Some(cat) => {
-            // 执行带条件的查询，处理 None 为 0
-            sqlx::query_scalar!(
-                "SELECT COUNT(*) FROM news WHERE news_type = $1",
-                cat
-            )
-            .fetch_one(&self.pool)
-            .await?
-            .unwrap_or(0) // 无结果时返回 0
-        }
-        None => {
-            // 执行全表查询，处理 None 为 0
-            sqlx::query_scalar!("SELECT COUNT(*) FROM news")
-                .fetch_one(&self.pool)
-                .await?
-                .unwrap_or(0) // 无结果时返回 0
-        }
-    };
-    Ok(count)
-}
-    //*/
 
-    // 在 NewsRepo 实现中添加
-    pub async fn get_paginated_by_category(
+    async fn query_news(
         &self,
+        filter: &NewsFilter,
+        sort: NewsSort,
         limit: u32,
         offset: i64,
-        category: &str,
     ) -> Result<Vec<NewsModel>, Error> {
-        sqlx::query_as!(
-            NewsModel,
-            r#"
-            SELECT id, news_type, href, title, datetime, content
-            FROM news
-            WHERE news_type = $1
-            ORDER BY datetime DESC
-            LIMIT $2 OFFSET $3
-            "#,
-            category,
-            limit as i64,
-            offset
-        )
-        .fetch_all(&self.pool)
-        .await
+        queries::query_news(&self.pool, filter, sort, limit, offset).await
     }
 
+    async fn query_news_keyset(
+        &self,
+        filter: &NewsFilter,
+        descending: bool,
+        keyset: Option<&Keyset>,
+        limit: u32,
+    ) -> Result<(Vec<NewsModel>, bool), Error> {
+        queries::query_news_keyset(&self.pool, filter, descending, keyset, limit).await
+    }
 
-}
+    async fn get_news_count(&self, filter: &NewsFilter) -> Result<i64, Error> {
+        queries::get_news_count(&self.pool, filter).await
+    }
 
+    // sqlx 的行流借用连接池和查询，无法直接返回；在后台任务中逐行读取，经有界通道送出。
+    // 接收端被丢弃（客户端断开）后发送失败，任务随之结束并释放连接
+    fn stream_news(&self, filter: NewsFilter) -> BoxStream<'static, Result<NewsModel, Error>> {
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(EXPORT_BUFFER);
+        tokio::spawn(async move {
+            let mut qb = queries::export_news_query(&filter);
+            let mut rows = qb.build_query_as::<NewsModel>().fetch(&pool);
+            while let Some(row) = rows.next().await {
+                let failed = row.is_err();
+                if tx.send(row).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|row| (row, rx)) }).boxed()
+    }
+
+    async fn increment_view_count(&self, id: i32) -> Result<(), Error> {
+        queries::increment_view_count(&self.pool, id).await
+    }
+
+    async fn add_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        queries::add_bookmark(&self.pool, user_id, news_id).await
+    }
+
+    async fn remove_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        queries::remove_bookmark(&self.pool, user_id, news_id).await
+    }
+
+    async fn list_bookmarks(&self, user_id: i32, limit: u32, offset: i64) -> Result<Vec<NewsModel>, Error> {
+        queries::list_bookmarks(&self.pool, user_id, limit, offset).await
+    }
+
+    async fn count_bookmarks(&self, user_id: i32) -> Result<i64, Error> {
+        queries::count_bookmarks(&self.pool, user_id).await
+    }
+
+    async fn bookmarked_ids(&self, user_id: i32, news_ids: &[i32]) -> Result<Vec<i32>, Error> {
+        queries::bookmarked_ids(&self.pool, user_id, news_ids).await
+    }
+
+    async fn record_read(&self, user_id: i32, news_id: i32) -> Result<(), Error> {
+        queries::record_read(&self.pool, user_id, news_id).await
+    }
+
+    async fn list_history(&self, user_id: i32, limit: u32, offset: i64) -> Result<Vec<HistoryRow>, Error> {
+        queries::list_history(&self.pool, user_id, limit, offset).await
+    }
+
+    async fn count_history(&self, user_id: i32) -> Result<i64, Error> {
+        queries::count_history(&self.pool, user_id).await
+    }
+
+    async fn remove_history(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        queries::remove_history(&self.pool, user_id, news_id).await
+    }
+
+    async fn clear_history(&self, user_id: i32) -> Result<u64, Error> {
+        queries::clear_history(&self.pool, user_id).await
+    }
+
+    async fn create_comment(
+        &self,
+        news_id: i32,
+        user_id: i32,
+        parent_id: Option<i32>,
+        root_id: Option<i32>,
+        content: &str,
+    ) -> Result<CommentRow, Error> {
+        queries::create_comment(&self.pool, news_id, user_id, parent_id, root_id, content).await
+    }
+
+    async fn get_comment(&self, id: i32) -> Result<Option<CommentRow>, Error> {
+        queries::get_comment(&self.pool, id).await
+    }
+
+    async fn update_comment_content(&self, id: i32, content: &str) -> Result<Option<CommentRow>, Error> {
+        queries::update_comment_content(&self.pool, id, content).await
+    }
+
+    async fn delete_comment(&self, id: i32) -> Result<bool, Error> {
+        queries::delete_comment(&self.pool, id).await
+    }
+
+    async fn set_comment_status(&self, id: i32, status: CommentStatus) -> Result<Option<CommentRow>, Error> {
+        queries::set_comment_status(&self.pool, id, status).await
+    }
+
+    async fn list_comment_threads(&self, news_id: i32, limit: u32, offset: i64) -> Result<Vec<CommentRow>, Error> {
+        queries::list_comment_threads(&self.pool, news_id, limit, offset).await
+    }
+
+    async fn count_comment_threads(&self, news_id: i32) -> Result<i64, Error> {
+        queries::count_comment_threads(&self.pool, news_id).await
+    }
+
+    async fn list_comments_by_status(&self, status: CommentStatus, limit: u32, offset: i64) -> Result<Vec<CommentRow>, Error> {
+        queries::list_comments_by_status(&self.pool, status, limit, offset).await
+    }
+
+    async fn count_comments_by_status(&self, status: CommentStatus) -> Result<i64, Error> {
+        queries::count_comments_by_status(&self.pool, status).await
+    }
+
+    async fn set_reaction(&self, user_id: i32, news_id: i32, reaction: ReactionKind) -> Result<(), Error> {
+        queries::set_reaction(&self.pool, user_id, news_id, reaction).await
+    }
+
+    async fn remove_reaction(&self, user_id: i32, news_id: i32) -> Result<bool, Error> {
+        queries::remove_reaction(&self.pool, user_id, news_id).await
+    }
+
+    async fn reaction_counts(&self, news_ids: &[i32]) -> Result<Vec<ReactionCount>, Error> {
+        queries::reaction_counts(&self.pool, news_ids).await
+    }
+
+    async fn user_reactions(&self, user_id: i32, news_ids: &[i32]) -> Result<Vec<UserReaction>, Error> {
+        queries::user_reactions(&self.pool, user_id, news_ids).await
+    }
+
+    async fn most_liked_news(
+        &self,
+        since: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<LikedNewsRow>, Error> {
+        queries::most_liked_news(&self.pool, since, category, limit).await
+    }
+
+    async fn trending_news(
+        &self,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<TrendingNewsRow>, Error> {
+        queries::trending_news(&self.pool, since, now, category, limit).await
+    }
+}
 
 // 用户仓库实现
 #[derive(Clone)]  // 新增 Clone 派生（如果 UsersRepo 也需要克隆）
@@ -154,57 +277,46 @@ impl UsersRepo {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+}
 
-    // 创建用户（强制非空字段）
-    pub async fn create_user(
-        &self,
-        name: &str,
-        email: &str,
-        password: &str,
-    ) -> Result<UserModel, Error> {
-        sqlx::query_as!(
-            UserModel,
-            r#"
-            INSERT INTO users (name, email, password)
-            VALUES ($1, $2, $3)
-            RETURNING id, name, email, password
-            "#,
-            name,
-            email,
-            password
-        )
-        .fetch_one(&self.pool)
-        .await
+#[async_trait]
+impl UserStore for UsersRepo {
+    async fn create_user(&self, name: &str, email: &str, password: &str) -> Result<UserModel, Error> {
+        queries::create_user(&self.pool, name, email, password).await
     }
 
-    // 根据邮箱查询用户
-    pub async fn get_user_by_email(&self, email: &str) -> Result<UserModel, Error> {
-        sqlx::query_as!(
-            UserModel,
-            r#"
-            SELECT id, name, email, password
-            FROM users
-            WHERE email = $1
-            "#,
-            email
-        )
-        .fetch_one(&self.pool)
-        .await
+    async fn get_user_by_email(&self, email: &str) -> Result<UserModel, Error> {
+        queries::get_user_by_email(&self.pool, email).await
     }
 
-    // 根据用户ID查询用户信息
-    pub async fn get_user_by_id(&self, user_id: i32) -> Result<UserModel, Error> {
-        sqlx::query_as!(
-            UserModel,
-            r#"
-            SELECT id, name, email, password
-            FROM users
-            WHERE id = $1
-            "#,
-            user_id
-        )
-        .fetch_one(&self.pool)
-        .await
+    async fn get_user_by_id(&self, user_id: i32) -> Result<UserModel, Error> {
+        queries::get_user_by_id(&self.pool, user_id).await
+    }
+
+    async fn update_password(&self, user_id: i32, password: &str) -> Result<(), Error> {
+        queries::update_password(&self.pool, user_id, password).await
+    }
+
+    async fn update_role(&self, user_id: i32, role: UserRole) -> Result<Option<UserModel>, Error> {
+        queries::update_role(&self.pool, user_id, role).await
+    }
+
+    async fn get_subscriptions(&self, user_id: i32) -> Result<Vec<String>, Error> {
+        queries::get_subscriptions(&self.pool, user_id).await
+    }
+
+    async fn set_subscriptions(&self, user_id: i32, categories: &[String]) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
+        queries::set_subscriptions(&mut tx, user_id, categories).await?;
+        tx.commit().await
+    }
+
+    async fn add_subscription(&self, user_id: i32, category: &str) -> Result<bool, Error> {
+        queries::add_subscription(&self.pool, user_id, category).await
+    }
+
+    async fn remove_subscription(&self, user_id: i32, category: &str) -> Result<bool, Error> {
+        queries::remove_subscription(&self.pool, user_id, category).await
     }
 }
 
@@ -212,41 +324,292 @@ impl UsersRepo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::pool::init_pool;
-    use sqlx::PgPool;
+    use crate::db::fixture::TestDb;
+    use crate::db::tokenizer::tokenize;
+
+    use crate::db::models::NewsCursor;
+
+    fn in_category(category: &str) -> NewsFilter {
+        NewsFilter { categories: vec![category.to_string()], ..NewsFilter::default() }
+    }
 
     #[actix_rt::test]
     async fn test_create_and_query_news() {
-        let pool = init_pool().await.unwrap();
-        let repo = NewsRepo::new(pool.clone());
-
-        // 使用事务回滚，避免污染测试数据库
-        let mut tx = pool.begin().await.unwrap();
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
 
         // 测试创建新闻
         let news = repo
-           .create_news("tech", "https://test.com", "Test News", "Content")
+           .create_news("tech", "https://test.com/1", "Test News", "Content", None)
            .await
            .unwrap();
         assert_eq!(news.title, "Test News");
 
         // 测试分页查询
-        let result = repo.get_paginated(1, 10).await.unwrap();
-        assert!(!result.is_empty());
+        let result = repo.query_news(&NewsFilter::default(), NewsSort::Newest, 10, 0).await.unwrap();
+        assert_eq!(result.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_queries_in_transaction() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+
+        // 事务内的写入只对该事务可见，回滚后不留痕迹
+        let mut tx = db.pool.begin().await.unwrap();
+        let news = queries::create_news(&mut *tx, "tech", "https://test.com/tx", "In Tx", "Content", None)
+           .await
+           .unwrap();
+        assert!(queries::get_news_by_id(&mut *tx, news.id).await.unwrap().is_some());
+        assert!(repo.get_news_by_id(news.id).await.unwrap().is_none());
+        tx.rollback().await.unwrap();
+        assert_eq!(repo.get_news_count(&NewsFilter::default()).await.unwrap(), 0);
+
+        // 提交后对连接池可见
+        let mut tx = db.pool.begin().await.unwrap();
+        let news = queries::create_news(&mut *tx, "tech", "https://test.com/tx", "In Tx", "Content", None)
+           .await
+           .unwrap();
+        queries::update_news(&mut tx, news.id, None, None, Some("Committed"), None, None).await.unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(repo.get_news_by_id(news.id).await.unwrap().unwrap().title, "Committed");
+    }
+
+    #[actix_rt::test]
+    async fn test_seeded_fixture() {
+        let db = TestDb::seeded().await;
+        let repo = NewsRepo::new(db.pool.clone());
 
-        tx.rollback().await.unwrap(); // 回滚事务
+        // news_data.csv 共 61 行，其中两篇文章在两个分类下重复出现
+        assert_eq!(repo.get_news_count(&NewsFilter::default()).await.unwrap(), 59);
+        assert_eq!(repo.get_news_count(&in_category("科技")).await.unwrap(), 5);
+
+        let hits = repo.search_news(&tokenize("马斯克"), None, 10, 0).await.unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().all(|n| n.title.contains("马斯克")));
     }
 
     #[actix_rt::test]
-    async fn test_create_and_query_user() {
-        let pool = init_pool().await.unwrap();
-        let repo = UsersRepo::new(pool.clone());
+    async fn test_query_news_filters_and_sort() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+
+        let stamp = Utc::now().timestamp_nanos_opt().unwrap();
+        let (cat_a, cat_b) = (format!("filter_a_{}", stamp), format!("filter_b_{}", stamp));
+        let at = |s: &str| Some(crate::news::time::parse_datetime(s).unwrap());
+        let old = repo
+           .create_news(&cat_a, &format!("https://test.com/{}/old", cat_a), "B old", "Content", at("2025-01-01 08:00:00"))
+           .await
+           .unwrap();
+        let new = repo
+           .create_news(&cat_a, &format!("https://test.com/{}/new", cat_a), "C new", "Content", at("2025-03-01 08:00:00"))
+           .await
+           .unwrap();
+        let other = repo
+           .create_news(&cat_b, &format!("https://test.com/{}/other", cat_b), "A other", "Content", at("2025-02-01 08:00:00"))
+           .await
+           .unwrap();
+        let ids = |rows: Vec<NewsModel>| rows.into_iter().map(|n| n.id).collect::<Vec<_>>();
+
+        // 多分类 + 各种排序
+        let both = NewsFilter { categories: vec![cat_a.clone(), cat_b.clone()], ..NewsFilter::default() };
+        assert_eq!(ids(repo.query_news(&both, NewsSort::Newest, 10, 0).await.unwrap()), vec![new.id, other.id, old.id]);
+        assert_eq!(ids(repo.query_news(&both, NewsSort::Oldest, 10, 0).await.unwrap()), vec![old.id, other.id, new.id]);
+        assert_eq!(ids(repo.query_news(&both, NewsSort::Title, 10, 0).await.unwrap()), vec![other.id, old.id, new.id]);
+        assert_eq!(ids(repo.query_news(&both, NewsSort::Newest, 1, 1).await.unwrap()), vec![other.id]);
+
+        repo.increment_view_count(old.id).await.unwrap();
+        assert_eq!(ids(repo.query_news(&both, NewsSort::Popular, 1, 0).await.unwrap()), vec![old.id]);
+
+        // 时间范围 [from, to)
+        let range = NewsFilter { from: at("2025-02-01 08:00:00"), to: at("2025-03-01 08:00:00"), ..both.clone() };
+        assert_eq!(ids(repo.query_news(&range, NewsSort::Newest, 10, 0).await.unwrap()), vec![other.id]);
+        assert_eq!(repo.get_news_count(&range).await.unwrap(), 1);
+
+        // 排除分类和排除 id
+        let excluded = NewsFilter { exclude_categories: vec![cat_b.clone()], exclude_ids: vec![old.id], ..both.clone() };
+        assert_eq!(ids(repo.query_news(&excluded, NewsSort::Newest, 10, 0).await.unwrap()), vec![new.id]);
+        assert_eq!(repo.get_news_count(&excluded).await.unwrap(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_query_news_keyset() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+
+        let category = format!("keyset_{}", Utc::now().timestamp_nanos_opt().unwrap());
+        let same_time = Some(Utc::now());
+        let mut created = Vec::new();
+        for n in 0..5 {
+            // 发布时间相同，依靠 id 区分顺序
+            let news = repo
+               .create_news(&category, &format!("https://test.com/{}/{}", category, n), "Keyset", "Content", same_time)
+               .await
+               .unwrap();
+            created.push(news.id);
+        }
+        created.reverse(); // newest 顺序
+        let filter = in_category(&category);
+        let ids = |rows: &[NewsModel]| rows.iter().map(|n| n.id).collect::<Vec<_>>();
+        let cursor = |n: &NewsModel| NewsCursor { time: n.published_at, id: n.id };
+
+        let (first, more) = repo.query_news_keyset(&filter, true, None, 2).await.unwrap();
+        assert_eq!(ids(&first), created[0..2]);
+        assert!(more);
+
+        // 翻页过程中插入新数据，不影响后续页
+        repo.create_news(&category, &format!("https://test.com/{}/late", category), "Late", "Content", None)
+           .await
+           .unwrap();
+
+        let after = Keyset::After(cursor(first.last().unwrap()));
+        let (second, _) = repo.query_news_keyset(&filter, true, Some(&after), 2).await.unwrap();
+        assert_eq!(ids(&second), created[2..4]);
+
+        let after = Keyset::After(cursor(second.last().unwrap()));
+        let (last, more) = repo.query_news_keyset(&filter, true, Some(&after), 2).await.unwrap();
+        assert_eq!(ids(&last), created[4..5]);
+        assert!(!more);
+
+        // 向前翻页返回展示顺序
+        let before = Keyset::Before(cursor(&last[0]));
+        let (prev, more) = repo.query_news_keyset(&filter, true, Some(&before), 2).await.unwrap();
+        assert_eq!(ids(&prev), created[2..4]);
+        assert!(more);
+
+        // oldest 顺序
+        let (oldest, _) = repo.query_news_keyset(&filter, false, None, 2).await.unwrap();
+        assert_eq!(ids(&oldest), vec![created[4], created[3]]);
+    }
+
+    #[actix_rt::test]
+    async fn test_get_news_by_id_and_related() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
 
-        // 使用事务回滚，避免污染测试数据库
-        let mut tx = pool.begin().await.unwrap();
+        let category = format!("related_{}", Utc::now().timestamp_nanos_opt().unwrap());
+        let news = repo
+           .create_news(&category, &format!("https://test.com/{}/a", category), "Main", "Content", None)
+           .await
+           .unwrap();
+        let other = repo
+           .create_news(&category, &format!("https://test.com/{}/b", category), "Other", "Content", None)
+           .await
+           .unwrap();
+
+        let found = repo.get_news_by_id(news.id).await.unwrap().unwrap();
+        assert_eq!(found.title, "Main");
+        assert!(repo.get_news_by_id(-1).await.unwrap().is_none());
+
+        // 相关新闻只包含同分类的其他新闻
+        let related = repo.get_related_news(&news, 5).await.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].id, other.id);
+    }
+
+    #[actix_rt::test]
+    async fn test_update_and_soft_delete_news() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+
+        let category = format!("delete_{}", Utc::now().timestamp_nanos_opt().unwrap());
+        let news = repo
+           .create_news(&category, &format!("https://test.com/{}/edit", category), "Before", "Content", None)
+           .await
+           .unwrap();
+
+        // 部分更新只修改传入的字段
+        let updated = repo
+           .update_news(news.id, None, None, Some("After"), None, None)
+           .await
+           .unwrap()
+           .unwrap();
+        assert_eq!(updated.title, "After");
+        assert_eq!(updated.content, "Content");
+
+        // 软删除后查询、计数和更新都不再可见
+        assert!(repo.soft_delete_news(news.id).await.unwrap());
+        assert!(!repo.soft_delete_news(news.id).await.unwrap());
+        assert!(repo.get_news_by_id(news.id).await.unwrap().is_none());
+        assert_eq!(repo.get_news_count(&in_category(&category)).await.unwrap(), 0);
+        assert!(repo.update_news(news.id, None, None, Some("X"), None, None).await.unwrap().is_none());
+
+        // 恢复后重新可见
+        let restored = repo.restore_news(news.id).await.unwrap().unwrap();
+        assert_eq!(restored.title, "After");
+        assert!(repo.restore_news(news.id).await.unwrap().is_none());
+        assert_eq!(repo.get_news_count(&in_category(&category)).await.unwrap(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_search_news() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+        let query = |text: &str| tokenize(text);
+
+        let category = format!("search_{}", Utc::now().timestamp_nanos_opt().unwrap());
+        let news = repo
+           .create_news(&category, &format!("https://test.com/{}/s1", category), "货轮太平洋起火", "载三千辆汽车", None)
+           .await
+           .unwrap();
+        let other = repo
+           .create_news(&category, &format!("https://test.com/{}/s2", category), "汽车板块上涨", "股市收盘", None)
+           .await
+           .unwrap();
+
+        // 标题命中的排名高于正文命中
+        let hits = repo.search_news(&query("汽车"), Some(&category), 10, 0).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].id, other.id);
+        let hits = repo.search_news(&query("太平洋"), Some(&category), 10, 0).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, news.id);
+        assert_eq!(repo.search_news_count(&query("太平洋"), Some(&category)).await.unwrap(), 1);
+
+        // 更新标题后检索向量随之更新
+        repo.update_news(news.id, None, None, Some("货轮失火"), None, None).await.unwrap();
+        assert_eq!(repo.search_news_count(&query("太平洋"), Some(&category)).await.unwrap(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_upsert_news_by_href() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+
+        let category = format!("upsert_{}", Utc::now().timestamp_nanos_opt().unwrap());
+        let href = format!("https://test.com/{}", category);
+
+        let inserted = match repo.upsert_news(&category, &href, "Title", "Content", None).await.unwrap() {
+            UpsertOutcome::Inserted(news) => news,
+            other => panic!("expected insert, got {:?}", other),
+        };
+
+        // 内容相同：不修改
+        let outcome = repo.upsert_news("其他", &href, "Title", "Content", None).await.unwrap();
+        assert!(matches!(outcome, UpsertOutcome::Unchanged));
+
+        // 标题变化：更新同一条记录，分类和时间保持不变
+        match repo.upsert_news("其他", &href, "New Title", "Content", None).await.unwrap() {
+            UpsertOutcome::Updated(news) => {
+                assert_eq!(news.id, inserted.id);
+                assert_eq!(news.news_type, category);
+                assert_eq!(news.published_at, inserted.published_at);
+            }
+            other => panic!("expected update, got {:?}", other),
+        }
+        assert_eq!(repo.get_news_count(&in_category(&category)).await.unwrap(), 1);
+
+        // 直接插入重复 href 违反唯一约束
+        assert!(repo.create_news(&category, &href, "Dup", "Content", None).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_create_and_query_user() {
+        let db = TestDb::new().await;
+        let repo = UsersRepo::new(db.pool.clone());
 
         // 生成唯一的邮箱地址，避免冲突
-        let timestamp = Utc::now().timestamp_nanos();
+        let timestamp = Utc::now().timestamp_nanos_opt().unwrap();
         let unique_email = format!("test_{}@example.com", timestamp);
 
         // 测试创建用户
@@ -263,7 +626,263 @@ mod tests {
         assert_eq!(queried_user.name, user.name);
         assert_eq!(queried_user.email, user.email);
         assert_eq!(queried_user.password, user.password);
+        assert_eq!(queried_user.role, UserRole::Reader); // 新用户默认为普通读者
+
+        // 测试修改角色
+        let promoted = repo.update_role(user.id, UserRole::Editor).await.unwrap().unwrap();
+        assert_eq!(promoted.role, UserRole::Editor);
+        assert!(repo.update_role(-1, UserRole::Admin).await.unwrap().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_bookmarks() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+        let user = UsersRepo::new(db.pool.clone()).create_user("reader", "reader@example.com", "x").await.unwrap();
+        let a = repo.create_news("tech", "https://bookmark.test/a", "A", "Content", None).await.unwrap();
+        let b = repo.create_news("tech", "https://bookmark.test/b", "B", "Content", None).await.unwrap();
+
+        assert!(repo.add_bookmark(user.id, a.id).await.unwrap());
+        assert!(repo.add_bookmark(user.id, b.id).await.unwrap());
+        assert!(!repo.add_bookmark(user.id, a.id).await.unwrap()); // 重复收藏
+        assert!(!repo.add_bookmark(user.id, -1).await.unwrap()); // 新闻不存在
+
+        // 最近收藏的在前
+        let listed = repo.list_bookmarks(user.id, 10, 0).await.unwrap();
+        assert_eq!(listed.iter().map(|n| n.id).collect::<Vec<_>>(), vec![b.id, a.id]);
+        assert_eq!(repo.list_bookmarks(user.id, 1, 1).await.unwrap()[0].id, a.id);
+        assert_eq!(repo.bookmarked_ids(user.id, &[a.id, -1]).await.unwrap(), vec![a.id]);
+
+        // 已删除的新闻不出现在收藏列表中，恢复后重新出现
+        repo.soft_delete_news(b.id).await.unwrap();
+        assert_eq!(repo.count_bookmarks(user.id).await.unwrap(), 1);
+        repo.restore_news(b.id).await.unwrap();
+        assert_eq!(repo.count_bookmarks(user.id).await.unwrap(), 2);
+
+        assert!(repo.remove_bookmark(user.id, a.id).await.unwrap());
+        assert!(!repo.remove_bookmark(user.id, a.id).await.unwrap());
+        assert_eq!(repo.list_bookmarks(user.id, 10, 0).await.unwrap().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_reading_history() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+        let user = UsersRepo::new(db.pool.clone()).create_user("reader", "reader@example.com", "x").await.unwrap();
+        let a = repo.create_news("tech", "https://history.test/a", "A", "Content", None).await.unwrap();
+        let b = repo.create_news("tech", "https://history.test/b", "B", "Content", None).await.unwrap();
+        let c = repo.create_news("tech", "https://history.test/c", "C", "Content", None).await.unwrap();
+
+        repo.record_read(user.id, a.id).await.unwrap();
+        repo.record_read(user.id, b.id).await.unwrap();
+        repo.record_read(user.id, a.id).await.unwrap(); // 再次阅读移到最前并累计次数
+
+        let history = repo.list_history(user.id, 10, 0).await.unwrap();
+        assert_eq!(history.iter().map(|h| h.id).collect::<Vec<_>>(), vec![a.id, b.id]);
+        assert_eq!(history[0].read_count, 2);
+        assert_eq!(repo.count_history(user.id).await.unwrap(), 2);
+
+        // unread_by 排除已读，分页与游标查询一致
+        let unread = NewsFilter { unread_by: Some(user.id), ..NewsFilter::default() };
+        assert_eq!(repo.get_news_count(&unread).await.unwrap(), 1);
+        let (page, _) = repo.query_news_keyset(&unread, true, None, 10).await.unwrap();
+        assert_eq!(page.iter().map(|n| n.id).collect::<Vec<_>>(), vec![c.id]);
+        let other = NewsFilter { unread_by: Some(user.id + 1), ..NewsFilter::default() };
+        assert_eq!(repo.get_news_count(&other).await.unwrap(), 3);
+
+        assert!(repo.remove_history(user.id, b.id).await.unwrap());
+        assert!(!repo.remove_history(user.id, b.id).await.unwrap());
+        assert_eq!(repo.clear_history(user.id).await.unwrap(), 1);
+        assert!(repo.list_history(user.id, 10, 0).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_category_subscriptions() {
+        let db = TestDb::new().await;
+        let repo = UsersRepo::new(db.pool.clone());
+        let user = repo.create_user("reader", "reader@example.com", "x").await.unwrap();
+
+        repo.set_subscriptions(user.id, &["科技".to_string(), "财经".to_string()]).await.unwrap();
+        assert_eq!(repo.get_subscriptions(user.id).await.unwrap(), vec!["科技", "财经"]);
+        // 整体替换
+        repo.set_subscriptions(user.id, &["体育".to_string()]).await.unwrap();
+        assert_eq!(repo.get_subscriptions(user.id).await.unwrap(), vec!["体育"]);
+
+        assert!(repo.add_subscription(user.id, "科技").await.unwrap());
+        assert!(!repo.add_subscription(user.id, "科技").await.unwrap());
+        assert!(repo.remove_subscription(user.id, "体育").await.unwrap());
+        assert!(!repo.remove_subscription(user.id, "体育").await.unwrap());
+        assert_eq!(repo.get_subscriptions(user.id).await.unwrap(), vec!["科技"]);
+    }
+
+    #[actix_rt::test]
+    async fn test_reactions() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+        let users = UsersRepo::new(db.pool.clone());
+        let alice = users.create_user("alice", "alice@example.com", "x").await.unwrap();
+        let bob = users.create_user("bob", "bob@example.com", "x").await.unwrap();
+        let a = repo.create_news("tech", "https://reactions.test/a", "A", "Content", None).await.unwrap();
+        let b = repo.create_news("tech", "https://reactions.test/b", "B", "Content", None).await.unwrap();
+        let c = repo.create_news("sports", "https://reactions.test/c", "C", "Content", None).await.unwrap();
+
+        repo.set_reaction(alice.id, a.id, ReactionKind::Like).await.unwrap();
+        repo.set_reaction(bob.id, a.id, ReactionKind::Like).await.unwrap();
+        repo.set_reaction(alice.id, b.id, ReactionKind::Love).await.unwrap();
+        repo.set_reaction(bob.id, c.id, ReactionKind::Like).await.unwrap();
+        // 每人每条新闻只有一个表态，再次表态会替换
+        repo.set_reaction(alice.id, b.id, ReactionKind::Like).await.unwrap();
+        repo.set_reaction(alice.id, b.id, ReactionKind::Like).await.unwrap();
+
+        let mut counts: Vec<(i32, ReactionKind, i64)> = repo
+            .reaction_counts(&[a.id, b.id])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.news_id, r.reaction, r.count))
+            .collect();
+        counts.sort();
+        assert_eq!(counts, vec![(a.id, ReactionKind::Like, 2), (b.id, ReactionKind::Like, 1)]);
+
+        let mine = repo.user_reactions(bob.id, &[a.id, b.id, c.id]).await.unwrap();
+        let mut mine: Vec<(i32, ReactionKind)> = mine.into_iter().map(|r| (r.news_id, r.reaction)).collect();
+        mine.sort();
+        assert_eq!(mine, vec![(a.id, ReactionKind::Like), (c.id, ReactionKind::Like)]);
+
+        // 按分类分组，组内按点赞数排序，每组取前 limit 条
+        let since = Utc::now() - chrono::Duration::days(7);
+        let liked = repo.most_liked_news(since, None, 1).await.unwrap();
+        let liked: Vec<(i32, i64)> = liked.into_iter().map(|r| (r.id, r.likes)).collect();
+        assert_eq!(liked, vec![(c.id, 1), (a.id, 2)]);
+        let liked = repo.most_liked_news(since, Some("tech"), 5).await.unwrap();
+        assert_eq!(liked.iter().map(|r| r.id).collect::<Vec<_>>(), vec![a.id, b.id]);
+        assert!(repo.most_liked_news(Utc::now() + chrono::Duration::days(1), None, 5).await.unwrap().is_empty());
+
+        // 已删除的新闻不进入排行
+        repo.soft_delete_news(a.id).await.unwrap();
+        let liked = repo.most_liked_news(since, Some("tech"), 5).await.unwrap();
+        assert_eq!(liked.iter().map(|r| r.id).collect::<Vec<_>>(), vec![b.id]);
+
+        assert!(repo.remove_reaction(alice.id, b.id).await.unwrap());
+        assert!(!repo.remove_reaction(alice.id, b.id).await.unwrap());
+        assert!(repo.reaction_counts(&[b.id]).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_stream_news() {
+        use futures_util::TryStreamExt;
+
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+        let base = Utc::now() - chrono::Duration::days(1);
+        // 超过导出缓冲区的行数，确保跨越多次发送
+        let mut created = Vec::new();
+        for i in 0..150 {
+            let category = if i % 3 == 0 { "sports" } else { "tech" };
+            let published_at = base + chrono::Duration::minutes(150 - i as i64);
+            let news = repo
+                .create_news(category, &format!("https://stream.test/{}", i), "T", "C", Some(published_at))
+                .await
+                .unwrap();
+            created.push(news);
+        }
+        repo.soft_delete_news(created[1].id).await.unwrap();
+
+        let all: Vec<NewsModel> = repo.stream_news(NewsFilter::default()).try_collect().await.unwrap();
+        assert_eq!(all.len(), 149);
+        // 按发布时间正序
+        assert_eq!(all.first().unwrap().id, created[149].id);
+        assert!(all.windows(2).all(|w| w[0].published_at <= w[1].published_at));
+
+        let sports: Vec<NewsModel> = repo.stream_news(in_category("sports")).try_collect().await.unwrap();
+        assert_eq!(sports.len(), 50);
+        assert!(sports.iter().all(|n| n.news_type == "sports"));
+
+        // 提前丢弃流不影响后续查询
+        let mut stream = repo.stream_news(NewsFilter::default());
+        stream.try_next().await.unwrap().unwrap();
+        drop(stream);
+        assert_eq!(repo.get_news_count(&NewsFilter::default()).await.unwrap(), 149);
+    }
+
+    #[actix_rt::test]
+    async fn test_trending_news() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+        let user = UsersRepo::new(db.pool.clone()).create_user("reader", "reader@example.com", "x").await.unwrap();
+        let a = repo.create_news("tech", "https://trending.test/a", "A", "Content", None).await.unwrap();
+        let b = repo.create_news("tech", "https://trending.test/b", "B", "Content", None).await.unwrap();
+        let c = repo.create_news("sports", "https://trending.test/c", "C", "Content", None).await.unwrap();
+
+        for _ in 0..3 {
+            repo.increment_view_count(a.id).await.unwrap();
+        }
+        repo.increment_view_count(c.id).await.unwrap();
+        repo.set_reaction(user.id, b.id, ReactionKind::Like).await.unwrap();
+        // 窗口之外的浏览量不计入
+        sqlx::query("INSERT INTO news_views (news_id, hour, views) VALUES ($1, date_trunc('hour', now()) - interval '3 days', 100)")
+            .bind(c.id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        let since = now - chrono::Duration::hours(24);
+        let rows = repo.trending_news(since, now, None, 10).await.unwrap();
+        let ranked: Vec<(i32, i64, i64)> = rows.iter().map(|r| (r.id, r.views, r.likes)).collect();
+        assert_eq!(ranked, vec![(b.id, 0, 1), (a.id, 3, 0), (c.id, 1, 0)]);
+        // 热度与内存实现使用同一公式
+        let age_hours = (now - a.published_at).num_milliseconds() as f64 / 3_600_000.0;
+        assert!((rows[1].score - crate::db::models::hotness(3, 0, age_hours)).abs() < 1e-6);
+
+        let rows = repo.trending_news(now - chrono::Duration::days(7), now, Some("sports"), 10).await.unwrap();
+        assert_eq!(rows.iter().map(|r| (r.id, r.views)).collect::<Vec<_>>(), vec![(c.id, 101)]);
+        assert_eq!(repo.trending_news(since, now, None, 1).await.unwrap().len(), 1);
+
+        repo.soft_delete_news(b.id).await.unwrap();
+        let rows = repo.trending_news(since, now, None, 10).await.unwrap();
+        assert_eq!(rows.iter().map(|r| r.id).collect::<Vec<_>>(), vec![a.id, c.id]);
+    }
+
+    #[actix_rt::test]
+    async fn test_comments() {
+        let db = TestDb::new().await;
+        let repo = NewsRepo::new(db.pool.clone());
+        let user = UsersRepo::new(db.pool.clone()).create_user("reader", "reader@example.com", "x").await.unwrap();
+        let news = repo.create_news("tech", "https://comments.test/a", "A", "Content", None).await.unwrap();
+
+        let first = repo.create_comment(news.id, user.id, None, None, "first").await.unwrap();
+        assert_eq!(first.author, "reader");
+        assert_eq!(first.status, CommentStatus::Pending);
+        let reply = repo.create_comment(news.id, user.id, Some(first.id), Some(first.id), "reply").await.unwrap();
+        let second = repo.create_comment(news.id, user.id, None, None, "second").await.unwrap();
+
+        // 讨论串按顶层评论分页，返回串内全部评论
+        assert_eq!(repo.count_comment_threads(news.id).await.unwrap(), 2);
+        let page = repo.list_comment_threads(news.id, 1, 0).await.unwrap();
+        assert_eq!(page.iter().map(|c| c.id).collect::<Vec<_>>(), vec![second.id]);
+        let page = repo.list_comment_threads(news.id, 1, 1).await.unwrap();
+        assert_eq!(page.iter().map(|c| c.id).collect::<Vec<_>>(), vec![first.id, reply.id]);
+
+        // 隐藏的顶层评论有可见回复时仍计入，回复也被隐藏后不再计入
+        let hidden = repo.set_comment_status(first.id, CommentStatus::Hidden).await.unwrap().unwrap();
+        assert_eq!(hidden.status, CommentStatus::Hidden);
+        assert_eq!(repo.count_comment_threads(news.id).await.unwrap(), 2);
+        assert!(repo.delete_comment(reply.id).await.unwrap());
+        assert!(!repo.delete_comment(reply.id).await.unwrap());
+        assert_eq!(repo.count_comment_threads(news.id).await.unwrap(), 1);
+        assert!(repo.get_comment(reply.id).await.unwrap().unwrap().deleted_at.is_some());
+
+        // 审核队列与修改后重新待审
+        assert_eq!(repo.count_comments_by_status(CommentStatus::Pending).await.unwrap(), 1);
+        repo.set_comment_status(second.id, CommentStatus::Approved).await.unwrap();
+        assert!(repo.list_comments_by_status(CommentStatus::Pending, 10, 0).await.unwrap().is_empty());
+        let edited = repo.update_comment_content(second.id, "edited").await.unwrap().unwrap();
+        assert_eq!((edited.content.as_str(), edited.status), ("edited", CommentStatus::Pending));
+        assert!(repo.update_comment_content(reply.id, "gone").await.unwrap().is_none());
 
-        tx.rollback().await.unwrap(); // 回滚事务
+        // 新闻删除后评论不出现在审核队列中
+        repo.soft_delete_news(news.id).await.unwrap();
+        assert_eq!(repo.count_comments_by_status(CommentStatus::Pending).await.unwrap(), 0);
     }
 }
\ No newline at end of file
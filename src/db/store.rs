@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use sqlx::Error;
+
+use crate::db::models::{CommentRow, CommentStatus, HistoryRow, Keyset, LikedNewsRow, NewsFilter, NewsModel, NewsSearchRow, NewsSort, ReactionCount, ReactionKind, TrendingNewsRow, UpsertOutcome, UserModel, UserReaction, UserRole};
+
+// 新闻存储接口：Postgres 实现见 repo::NewsRepo，内存实现见 memory::MemoryStore（用于测试）
+#[async_trait]
+pub trait NewsStore: Send + Sync {
+    // 创建新闻；未提供发布时间时以入库时间作为发布时间，href 重复时返回唯一约束错误
+    async fn create_news(
+        &self,
+        news_type: &str,
+        href: &str,
+        title: &str,
+        content: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<NewsModel, Error>;
+
+    // 按 href 幂等写入新闻
+    async fn upsert_news(
+        &self,
+        news_type: &str,
+        href: &str,
+        title: &str,
+        content: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<UpsertOutcome, Error>;
+
+    // 根据 ID 查询单条未删除的新闻
+    async fn get_news_by_id(&self, id: i32) -> Result<Option<NewsModel>, Error>;
+
+    // 同分类下发布时间最接近的若干条（排除自身）
+    async fn get_related_news(&self, news: &NewsModel, limit: u32) -> Result<Vec<NewsModel>, Error>;
+
+    // 部分更新，None 字段保持原值；已删除或不存在时返回 None
+    async fn update_news(
+        &self,
+        id: i32,
+        news_type: Option<&str>,
+        href: Option<&str>,
+        title: Option<&str>,
+        content: Option<&str>,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<NewsModel>, Error>;
+
+    // 为缺少检索索引的新闻补建索引，返回处理条数
+    async fn reindex_missing_search_vectors(&self, batch_size: u32) -> Result<u64, Error>;
+
+    // 全文检索：terms 为 tokenizer::tokenize 切出的检索词（取 AND），结果按相关度排序
+    async fn search_news(
+        &self,
+        terms: &[String],
+        category: Option<&str>,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Vec<NewsSearchRow>, Error>;
+
+    async fn search_news_count(&self, terms: &[String], category: Option<&str>) -> Result<i64, Error>;
+
+    // 软删除，返回是否有记录被删除
+    async fn soft_delete_news(&self, id: i32) -> Result<bool, Error>;
+
+    // 恢复已软删除的新闻；未删除或不存在时返回 None
+    async fn restore_news(&self, id: i32) -> Result<Option<NewsModel>, Error>;
+
+    // 按过滤条件、排序方式分页查询
+    async fn query_news(
+        &self,
+        filter: &NewsFilter,
+        sort: NewsSort,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Vec<NewsModel>, Error>;
+
+    // 游标分页，返回展示顺序的结果以及游标方向上是否还有更多数据
+    async fn query_news_keyset(
+        &self,
+        filter: &NewsFilter,
+        descending: bool,
+        keyset: Option<&Keyset>,
+        limit: u32,
+    ) -> Result<(Vec<NewsModel>, bool), Error>;
+
+    async fn get_news_count(&self, filter: &NewsFilter) -> Result<i64, Error>;
+
+    // 逐条读取符合条件的全部新闻（导出用），按时间正序；出错时流中返回错误后结束
+    fn stream_news(&self, filter: NewsFilter) -> BoxStream<'static, Result<NewsModel, Error>>;
+
+    async fn increment_view_count(&self, id: i32) -> Result<(), Error>;
+
+    // 收藏未删除的新闻，返回是否新增（已收藏或新闻不存在时为 false）
+    async fn add_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, Error>;
+
+    // 返回是否有收藏被删除
+    async fn remove_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, Error>;
+
+    // 用户收藏的未删除新闻，最近收藏的在前
+    async fn list_bookmarks(&self, user_id: i32, limit: u32, offset: i64) -> Result<Vec<NewsModel>, Error>;
+
+    async fn count_bookmarks(&self, user_id: i32) -> Result<i64, Error>;
+
+    // news_ids 中被用户收藏的 ID
+    async fn bookmarked_ids(&self, user_id: i32, news_ids: &[i32]) -> Result<Vec<i32>, Error>;
+
+    // 记录一次阅读，同一篇新闻只保留一条记录
+    async fn record_read(&self, user_id: i32, news_id: i32) -> Result<(), Error>;
+
+    // 用户读过的未删除新闻，最近阅读的在前
+    async fn list_history(&self, user_id: i32, limit: u32, offset: i64) -> Result<Vec<HistoryRow>, Error>;
+
+    async fn count_history(&self, user_id: i32) -> Result<i64, Error>;
+
+    // 返回是否有记录被删除
+    async fn remove_history(&self, user_id: i32, news_id: i32) -> Result<bool, Error>;
+
+    // 清空阅读记录，返回删除条数
+    async fn clear_history(&self, user_id: i32) -> Result<u64, Error>;
+
+    // 发表评论；回复时 root_id 为所在讨论串的顶层评论
+    async fn create_comment(
+        &self,
+        news_id: i32,
+        user_id: i32,
+        parent_id: Option<i32>,
+        root_id: Option<i32>,
+        content: &str,
+    ) -> Result<CommentRow, Error>;
+
+    // 根据 ID 查询评论（包括已删除的）
+    async fn get_comment(&self, id: i32) -> Result<Option<CommentRow>, Error>;
+
+    // 修改内容并重新进入审核队列；已删除或不存在时返回 None
+    async fn update_comment_content(&self, id: i32, content: &str) -> Result<Option<CommentRow>, Error>;
+
+    // 软删除，返回是否有评论被删除
+    async fn delete_comment(&self, id: i32) -> Result<bool, Error>;
+
+    // 修改审核状态；已删除或不存在时返回 None
+    async fn set_comment_status(&self, id: i32, status: CommentStatus) -> Result<Option<CommentRow>, Error>;
+
+    // 按顶层评论分页返回讨论串中的全部评论（按发表时间正序），只统计自身可见或有可见回复的顶层评论
+    async fn list_comment_threads(&self, news_id: i32, limit: u32, offset: i64) -> Result<Vec<CommentRow>, Error>;
+
+    async fn count_comment_threads(&self, news_id: i32) -> Result<i64, Error>;
+
+    // 审核队列：指定状态的未删除评论，最早的在前
+    async fn list_comments_by_status(&self, status: CommentStatus, limit: u32, offset: i64) -> Result<Vec<CommentRow>, Error>;
+
+    async fn count_comments_by_status(&self, status: CommentStatus) -> Result<i64, Error>;
+
+    // 设置表态，已有表态时替换
+    async fn set_reaction(&self, user_id: i32, news_id: i32, reaction: ReactionKind) -> Result<(), Error>;
+
+    // 返回是否有表态被删除
+    async fn remove_reaction(&self, user_id: i32, news_id: i32) -> Result<bool, Error>;
+
+    // news_ids 中各新闻的各表态数量（数量为 0 的不返回）
+    async fn reaction_counts(&self, news_ids: &[i32]) -> Result<Vec<ReactionCount>, Error>;
+
+    async fn user_reactions(&self, user_id: i32, news_ids: &[i32]) -> Result<Vec<UserReaction>, Error>;
+
+    // since 之后点赞最多的新闻，每个分类取前 limit 条，按分类、点赞数排序
+    async fn most_liked_news(
+        &self,
+        since: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<LikedNewsRow>, Error>;
+
+    // since 之后有浏览或点赞的新闻，按 now 时刻的热度倒序
+    async fn trending_news(
+        &self,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<TrendingNewsRow>, Error>;
+}
+
+// 用户存储接口：Postgres 实现见 repo::UsersRepo
+#[async_trait]
+pub trait UserStore: Send + Sync {
+    // 创建用户，邮箱重复时返回唯一约束错误
+    async fn create_user(&self, name: &str, email: &str, password: &str) -> Result<UserModel, Error>;
+
+    // 用户不存在时返回 RowNotFound
+    async fn get_user_by_email(&self, email: &str) -> Result<UserModel, Error>;
+
+    // 用户不存在时返回 RowNotFound
+    async fn get_user_by_id(&self, user_id: i32) -> Result<UserModel, Error>;
+
+    async fn update_password(&self, user_id: i32, password: &str) -> Result<(), Error>;
+
+    // 用户不存在时返回 None
+    async fn update_role(&self, user_id: i32, role: UserRole) -> Result<Option<UserModel>, Error>;
+
+    // 用户订阅的分类，按名称排序
+    async fn get_subscriptions(&self, user_id: i32) -> Result<Vec<String>, Error>;
+
+    // 整体替换订阅的分类
+    async fn set_subscriptions(&self, user_id: i32, categories: &[String]) -> Result<(), Error>;
+
+    // 返回是否新增
+    async fn add_subscription(&self, user_id: i32, category: &str) -> Result<bool, Error>;
+
+    // 返回是否有订阅被删除
+    async fn remove_subscription(&self, user_id: i32, category: &str) -> Result<bool, Error>;
+}
@@ -0,0 +1,67 @@
+use jieba_rs::Jieba;
+use std::sync::OnceLock;
+
+// Postgres 内置解析器不支持中文分词（且在 C locale 下会直接丢弃中文字符），
+// 这里用 jieba 切词后直接拼出 tsvector / tsquery 字面量，由数据库按类型转换，
+// 不依赖数据库的分词配置。
+fn jieba() -> &'static Jieba {
+    static JIEBA: OnceLock<Jieba> = OnceLock::new();
+    JIEBA.get_or_init(Jieba::new)
+}
+
+// 切分为检索词：去掉标点和空白，英文统一小写
+pub fn tokenize(text: &str) -> Vec<String> {
+    jieba()
+        .cut_for_search(text, true)
+        .into_iter()
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+// 检索词加引号；tokenize 只保留字母数字，转义仅作保险
+fn quote(token: &str) -> String {
+    format!("'{}'", token.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+// 生成 tsvector 字面量：标题权重 A，正文权重 B，位置连续编号
+pub fn search_vector(title: &str, content: &str) -> String {
+    let title_tokens = tokenize(title).into_iter().map(|t| (t, 'A'));
+    let content_tokens = tokenize(content).into_iter().map(|t| (t, 'B'));
+
+    title_tokens
+        .chain(content_tokens)
+        .enumerate()
+        .map(|(i, (token, weight))| format!("{}:{}{}", quote(&token), i + 1, weight))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 生成 tsquery 字面量（各检索词取 AND），没有有效检索词时返回 None
+pub fn search_query(terms: &[String]) -> Option<String> {
+    if terms.is_empty() {
+        return None;
+    }
+    Some(terms.iter().map(|t| quote(t)).collect::<Vec<_>>().join(" & "))
+}
+
+// 在 db/tokenizer.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_chinese() {
+        let tokens = tokenize("载3000辆汽车货轮太平洋起火！");
+        assert!(tokens.contains(&"汽车".to_string()));
+        assert!(tokens.contains(&"太平洋".to_string()));
+        assert!(!tokens.iter().any(|t| t == "！"));
+    }
+
+    #[test]
+    fn test_search_vector_and_query() {
+        assert_eq!(search_vector("Hello, World", "rust"), "'hello':1A 'world':2A 'rust':3B");
+        assert_eq!(search_query(&tokenize("Hello World")).unwrap(), "'hello' & 'world'");
+        assert!(search_query(&tokenize("  ，。 ")).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::ingest::source::{FeedItem, NewsSource};
+use crate::news::models::{IngestSummary, NewsCreate};
+use crate::news::service::NewsService;
+
+// 采集参数
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    pub per_category: usize, // 每个分类最多采集的条数
+    pub item_count: u32,     // 每次请求的条目数
+    pub max_pages: u32,      // 每个分类最多翻页次数，避免数据源异常时死循环
+    pub delay: Duration,     // 两次请求之间的间隔
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            per_category: 5,
+            item_count: 20,
+            max_pages: 10,
+            delay: Duration::from_secs(2),
+        }
+    }
+}
+
+// 把数据源条目规范化为创建新闻的请求体；没有摘要时用标题填充正文
+pub fn to_news_create(category: &str, item: FeedItem) -> NewsCreate {
+    let content = item.summary.unwrap_or_else(|| item.title.clone());
+    NewsCreate {
+        news_type: category.to_string(),
+        href: item.url,
+        title: item.title,
+        content,
+        published_at: item.published_at,
+    }
+}
+
+pub struct Crawler<S> {
+    source: S,
+    service: NewsService,
+    options: CrawlOptions,
+}
+
+impl<S: NewsSource> Crawler<S> {
+    pub fn new(source: S, service: NewsService, options: CrawlOptions) -> Self {
+        Self { source, service, options }
+    }
+
+    // 依次采集各分类，channels 为 (分类名, 频道 ID)
+    pub async fn run(&self, channels: &[(&str, &str)]) -> IngestSummary {
+        let mut report = IngestSummary::default();
+        for (category, channel_id) in channels {
+            log::info!("开始采集 {} 分类的新闻...", category);
+            self.crawl_category(category, channel_id, &mut report).await;
+        }
+        report
+    }
+
+    async fn crawl_category(&self, category: &str, channel_id: &str, report: &mut IngestSummary) {
+        let mut seen = HashSet::new();
+        let mut saved = 0;
+
+        for page in 1..=self.options.max_pages {
+            if saved >= self.options.per_category {
+                break;
+            }
+            if page > 1 && !self.options.delay.is_zero() {
+                actix_web::rt::time::sleep(self.options.delay).await;
+            }
+
+            let items = match self.source.fetch_channel(channel_id, page, self.options.item_count).await {
+                Ok(items) => items,
+                Err(e) => {
+                    log::error!("Failed to fetch channel {} page {}: {}", channel_id, page, e);
+                    report.failed += 1;
+                    break;
+                }
+            };
+            if items.is_empty() {
+                break;
+            }
+
+            for mut item in items {
+                if saved >= self.options.per_category {
+                    break;
+                }
+                if item.title.is_empty() || item.url.is_empty() || !seen.insert(item.url.clone()) {
+                    continue;
+                }
+                if item.published_at.is_none() {
+                    item.published_at = self.source.fetch_published_time(&item.url).await.unwrap_or_else(|e| {
+                        log::warn!("Failed to fetch published time of {}: {}", item.url, e);
+                        None
+                    });
+                }
+                // 按 href 幂等写入，重复采集不会产生重复记录
+                match self.service.upsert_news(to_news_create(category, item)).await {
+                    Ok(outcome) => {
+                        saved += 1;
+                        report.record(&outcome);
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to save news in {}: {}", category, e);
+                        report.failed += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 在 ingest/crawler.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::NewsFilter;
+    use crate::db::memory::MemoryStore;
+    use crate::db::store::NewsStore;
+    use crate::ingest::source::IngestError;
+    use crate::news::dao::NewsDao;
+    use async_trait::async_trait;
+    use chrono::Utc;
+
+    // 固定返回两页数据的假数据源，第二页包含重复条目
+    struct StubSource {
+        prefix: String,
+    }
+
+    #[async_trait(?Send)]
+    impl NewsSource for StubSource {
+        async fn fetch_channel(&self, _channel_id: &str, page: u32, _item_count: u32) -> Result<Vec<FeedItem>, IngestError> {
+            let item = |n: u32| FeedItem {
+                title: format!("Stub {}", n),
+                url: format!("{}/{}", self.prefix, n),
+                summary: None,
+                published_at: None,
+            };
+            Ok(match page {
+                1 => vec![item(1), item(2)],
+                2 => vec![item(2), item(3), item(4)],
+                _ => vec![],
+            })
+        }
+    }
+
+    #[test]
+    fn test_to_news_create() {
+        let item = FeedItem {
+            title: "Title".into(),
+            url: "https://example.com".into(),
+            summary: Some("Summary".into()),
+            published_at: Some(Utc::now()),
+        };
+        let data = to_news_create("科技", item);
+        assert_eq!(data.news_type, "科技");
+        assert_eq!(data.content, "Summary");
+        assert!(data.published_at.is_some());
+    }
+
+    #[actix_rt::test]
+    async fn test_crawler_saves_through_service() {
+        let store = MemoryStore::new();
+        let service = NewsService::new(NewsDao::new(store.clone()), store.clone());
+
+        let category = format!("ingest_{}", Utc::now().timestamp_nanos_opt().unwrap());
+        let source = StubSource { prefix: format!("https://stub.test/{}", category) };
+        let options = CrawlOptions { per_category: 3, delay: Duration::ZERO, ..CrawlOptions::default() };
+
+        let report = Crawler::new(source, service.clone(), options)
+            .run(&[(category.as_str(), "stub")])
+            .await;
+        assert_eq!(report, IngestSummary { inserted: 3, ..IngestSummary::default() });
+
+        let count = store.get_news_count(&NewsFilter { categories: vec![category.clone()], ..NewsFilter::default() }).await.unwrap();
+        assert_eq!(count, 3);
+
+        // 再次采集同样的数据不会重复写入
+        let source = StubSource { prefix: format!("https://stub.test/{}", category) };
+        let options = CrawlOptions { per_category: 3, delay: Duration::ZERO, ..CrawlOptions::default() };
+        let report = Crawler::new(source, service, options)
+            .run(&[(category.as_str(), "stub")])
+            .await;
+        assert_eq!(report, IngestSummary { unchanged: 3, ..IngestSummary::default() });
+    }
+}
@@ -0,0 +1,8 @@
+pub mod crawler;
+pub mod source;
+pub mod tencent;
+
+// 导出公共接口
+pub use crawler::{CrawlOptions, Crawler};
+pub use source::{FeedItem, IngestError, NewsSource};
+pub use tencent::{TencentSource, CATEGORY_CHANNELS};
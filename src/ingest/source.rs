@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::news::service::NewsError;
+
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("Service error: {0}")]
+    Service(#[from] NewsError),
+}
+
+// 数据源返回的原始条目
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedItem {
+    pub title: String,
+    pub url: String,
+    pub summary: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+// 新闻数据源：按频道分页拉取条目，HTTP 实现之外也便于在测试中替换
+#[async_trait(?Send)]
+pub trait NewsSource {
+    async fn fetch_channel(
+        &self,
+        channel_id: &str,
+        page: u32,
+        item_count: u32,
+    ) -> Result<Vec<FeedItem>, IngestError>;
+
+    // 列表中没有发布时间时，从文章页获取；默认不支持
+    async fn fetch_published_time(&self, _url: &str) -> Result<Option<DateTime<Utc>>, IngestError> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,221 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE, USER_AGENT};
+use serde_json::{json, Value};
+
+use crate::ingest::source::{FeedItem, IngestError, NewsSource};
+use crate::news::time::parse_datetime;
+
+pub const DEFAULT_FEED_URL: &str = "https://i.news.qq.com/web_feed/getHotModuleList";
+
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/112.0.0.0 Safari/537.36";
+
+// 分类与腾讯新闻频道 ID 的对应关系（与原 TencentSpider.py 中的 category_ids 一致）
+pub const CATEGORY_CHANNELS: &[(&str, &str)] = &[
+    ("要闻", "news_news_top"),
+    ("财经", "news_news_finance"),
+    ("科技", "news_news_tech"),
+    ("娱乐", "news_news_ent"),
+    ("体育", "news_news_sports"),
+    ("国际", "news_news_world"),
+    ("军事", "news_news_mil"),
+    ("汽车", "news_news_auto"),
+    ("教育", "news_news_edu"),
+    ("健康", "news_news_antip"),
+    ("游戏", "news_news_game"),
+    ("科学", "news_news_kepu"),
+    ("历史", "news_news_history"),
+];
+
+// 腾讯新闻 web_feed 接口
+pub struct TencentSource {
+    client: reqwest::Client,
+    feed_url: String,
+    device_id: String,
+}
+
+impl TencentSource {
+    // cookie 可选，由调用方从环境变量等处传入，不再硬编码在代码里
+    pub fn new(feed_url: &str, cookie: Option<&str>) -> Result<Self, IngestError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(DEFAULT_USER_AGENT));
+        if let Some(cookie) = cookie {
+            let value = HeaderValue::from_str(cookie)
+                .map_err(|e| IngestError::InvalidResponse(format!("invalid cookie: {}", e)))?;
+            headers.insert(COOKIE, value);
+        }
+
+        let client = reqwest::Client::builder().default_headers(headers).build()?;
+        Ok(Self {
+            client,
+            feed_url: feed_url.to_string(),
+            device_id: String::new(),
+        })
+    }
+
+    pub fn with_device_id(mut self, device_id: &str) -> Self {
+        self.device_id = device_id.to_string();
+        self
+    }
+}
+
+// 递归收集同时带有 title 和 url 字段的对象（对应原脚本的 $..title / $..url）
+fn collect_items(value: &Value, items: &mut Vec<FeedItem>) {
+    match value {
+        Value::Object(map) => {
+            if let (Some(Value::String(title)), Some(Value::String(url))) = (map.get("title"), map.get("url")) {
+                let summary = ["abstract", "desc"]
+                    .iter()
+                    .filter_map(|key| map.get(*key).and_then(Value::as_str))
+                    .map(str::trim)
+                    .find(|s| !s.is_empty())
+                    .map(str::to_string);
+                // 列表接口的时间不带时区，parse_datetime 按 UTC+8 解释
+                let published_at = ["publish_time", "pub_time", "time"]
+                    .iter()
+                    .filter_map(|key| map.get(*key).and_then(Value::as_str))
+                    .find_map(parse_datetime);
+                items.push(FeedItem {
+                    title: title.trim().to_string(),
+                    url: url.trim().to_string(),
+                    summary,
+                    published_at,
+                });
+                return;
+            }
+            map.values().for_each(|v| collect_items(v, items));
+        }
+        Value::Array(values) => values.iter().for_each(|v| collect_items(v, items)),
+        _ => {}
+    }
+}
+
+// 从文章页 HTML 中读取 <meta property="article:published_time" content="...">
+pub fn parse_published_time(html: &str) -> Option<DateTime<Utc>> {
+    let marker = "property=\"article:published_time\"";
+    let pos = html.find(marker)?;
+    let tag_start = html[..pos].rfind('<')?;
+    let tag_end = pos + html[pos..].find('>')?;
+    let tag = &html[tag_start..tag_end];
+
+    let content_start = tag.find("content=\"")? + "content=\"".len();
+    let content_len = tag[content_start..].find('"')?;
+    parse_datetime(&tag[content_start..content_start + content_len])
+}
+
+pub fn parse_feed(body: &Value) -> Vec<FeedItem> {
+    let mut items = Vec::new();
+    collect_items(body, &mut items);
+    items
+}
+
+#[async_trait(?Send)]
+impl NewsSource for TencentSource {
+    async fn fetch_channel(
+        &self,
+        channel_id: &str,
+        page: u32,
+        item_count: u32,
+    ) -> Result<Vec<FeedItem>, IngestError> {
+        let payload = json!({
+            "se_req": { "from": "pc" },
+            "forward": "2",
+            "qimei36": self.device_id,
+            "device_id": self.device_id,
+            "base_req": { "from": "pc" },
+            "channel_id": channel_id,
+            "flush_num": page,
+            "item_count": item_count,
+        });
+
+        let resp = self.client.post(&self.feed_url).json(&payload).send().await?;
+        if !resp.status().is_success() {
+            return Err(IngestError::InvalidResponse(format!(
+                "channel {} returned status {}",
+                channel_id,
+                resp.status()
+            )));
+        }
+        let body: Value = resp.json().await?;
+        Ok(parse_feed(&body))
+    }
+
+    async fn fetch_published_time(&self, url: &str) -> Result<Option<DateTime<Utc>>, IngestError> {
+        let resp = self.client.get(url).send().await?;
+        if !resp.status().is_success() {
+            return Ok(None);
+        }
+        Ok(parse_published_time(&resp.text().await?))
+    }
+}
+
+// 在 ingest/tencent.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+
+    fn fixture() -> Value {
+        json!({
+            "ret": 0,
+            "data": {
+                "list": [
+                    { "id": "a1", "title": " 货轮太平洋起火 ", "url": "https://new.qq.com/rain/a/1", "abstract": "载3000辆汽车", "publish_time": "2025-06-06 08:09:10" },
+                    { "id": "a2", "title": "马斯克回应", "url": "https://new.qq.com/rain/a/2", "chl_name": "新闻" }
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_feed() {
+        let items = parse_feed(&fixture());
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "货轮太平洋起火");
+        assert_eq!(items[0].summary.as_deref(), Some("载3000辆汽车"));
+        assert_eq!(items[0].published_at.unwrap().to_rfc3339(), "2025-06-06T00:09:10+00:00");
+        assert_eq!(items[1].url, "https://new.qq.com/rain/a/2");
+        assert_eq!(items[1].summary, None);
+        assert_eq!(items[1].published_at, None);
+    }
+
+    #[test]
+    fn test_parse_published_time() {
+        let html = r#"<head><meta name="x" content="y"><meta property="article:published_time" content="2025-06-06 08:09:10" /></head>"#;
+        assert_eq!(parse_published_time(html).unwrap().to_rfc3339(), "2025-06-06T00:09:10+00:00");
+        assert!(parse_published_time("<head></head>").is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_fetch_channel_from_fixture_server() {
+        // 本地假服务器：校验请求体中的频道 ID 后返回固定数据
+        let server = HttpServer::new(|| {
+            App::new()
+                .route("/feed", web::post().to(|body: web::Json<Value>| async move {
+                    if body["channel_id"] == "news_news_tech" && body["flush_num"] == 1 {
+                        HttpResponse::Ok().json(fixture())
+                    } else {
+                        HttpResponse::Ok().json(json!({ "ret": 0, "data": { "list": [] } }))
+                    }
+                }))
+                .route("/article", web::get().to(|| async {
+                    HttpResponse::Ok()
+                        .content_type("text/html")
+                        .body(r#"<meta property="article:published_time" content="2025-06-06T08:09:10+08:00">"#)
+                }))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        actix_rt::spawn(server.run());
+
+        let source = TencentSource::new(&format!("http://{}/feed", addr), Some("a=b")).unwrap();
+        let items = source.fetch_channel("news_news_tech", 1, 20).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(source.fetch_channel("news_news_tech", 2, 20).await.unwrap().is_empty());
+
+        let published = source.fetch_published_time(&format!("http://{}/article", addr)).await.unwrap();
+        assert_eq!(published.unwrap().to_rfc3339(), "2025-06-06T00:09:10+00:00");
+    }
+}
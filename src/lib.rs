@@ -1,11 +1,15 @@
+pub mod config;
 pub mod db;
 pub mod news;
+pub mod ingest;
+pub mod session;
 
 // 公共类型定义
 pub type AppResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
 pub type AppDbPool = sqlx::Pool<sqlx::Postgres>;
 
 // 导出核心模块
+pub use config::Config;
 pub use db::repo::{NewsRepo, UsersRepo};
 pub use news::{
     dao::NewsDao, 
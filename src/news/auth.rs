@@ -0,0 +1,191 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_session::SessionExt;
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+
+use crate::db::models::UserRole;
+use crate::news::error::NewsError;
+use crate::news::service::NewsService;
+
+// 当前登录用户：从 session 中的 user_id 解析，并查询最新的角色
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub role: UserRole,
+}
+
+impl CurrentUser {
+    // 校验角色不低于 role
+    pub fn require(&self, role: UserRole) -> Result<(), NewsError> {
+        if self.role >= role {
+            Ok(())
+        } else {
+            Err(NewsError::Forbidden("Insufficient permissions".into()))
+        }
+    }
+}
+
+impl FromRequest for CurrentUser {
+    type Error = NewsError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let session = req.get_session();
+        let service = req.app_data::<web::Data<NewsService>>().cloned();
+
+        Box::pin(async move {
+            let user_id = match session.get::<i32>("user_id") {
+                Ok(Some(user_id)) => user_id,
+                Ok(None) => return Err(NewsError::Unauthorized("User not logged in".into())),
+                Err(e) => {
+                    return Err(NewsError::Internal(format!("Error getting user_id from session: {}", e)));
+                }
+            };
+            let service = service.ok_or_else(|| NewsError::Internal("NewsService is not configured".into()))?;
+
+            match service.get_user_by_id(user_id).await {
+                Ok(user) => Ok(CurrentUser {
+                    id: user.id,
+                    name: user.name,
+                    email: user.email,
+                    role: user.role,
+                }),
+                Err(NewsError::NotFound(_)) => {
+                    // session 中的用户已不存在，视为未登录
+                    log::warn!("Session user {} no longer exists", user_id);
+                    session.purge();
+                    Err(NewsError::Unauthorized("User not logged in".into()))
+                }
+                // 数据库等临时故障不影响登录状态
+                Err(e) => Err(e),
+            }
+        })
+    }
+}
+
+// 要求编辑及以上角色的用户
+#[derive(Debug, Clone)]
+pub struct EditorUser(pub CurrentUser);
+
+impl FromRequest for EditorUser {
+    type Error = NewsError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let user = CurrentUser::from_request(req, payload);
+        Box::pin(async move {
+            let user = user.await?;
+            user.require(UserRole::Editor)?;
+            Ok(EditorUser(user))
+        })
+    }
+}
+
+// 要求管理员角色的用户
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub CurrentUser);
+
+impl FromRequest for AdminUser {
+    type Error = NewsError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let user = CurrentUser::from_request(req, payload);
+        Box::pin(async move {
+            let user = user.await?;
+            user.require(UserRole::Admin)?;
+            Ok(AdminUser(user))
+        })
+    }
+}
+
+// 在 news/auth.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory::MemoryStore;
+    use crate::db::repo::{NewsRepo, UsersRepo};
+    use crate::news::dao::NewsDao;
+    use actix_session::{storage::CookieSessionStore, Session, SessionMiddleware};
+    use actix_web::{cookie::Key, http::StatusCode, test as actix_test, App, HttpResponse};
+    use sqlx::postgres::PgPoolOptions;
+    use std::time::Duration;
+
+    fn user_with_role(role: UserRole) -> CurrentUser {
+        CurrentUser {
+            id: 1,
+            name: "test_user".into(),
+            email: "test@example.com".into(),
+            role,
+        }
+    }
+
+    #[test]
+    fn test_require_role() {
+        assert!(user_with_role(UserRole::Admin).require(UserRole::Editor).is_ok());
+        assert!(user_with_role(UserRole::Editor).require(UserRole::Editor).is_ok());
+        assert!(matches!(
+            user_with_role(UserRole::Reader).require(UserRole::Editor),
+            Err(NewsError::Forbidden(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_editor_requires_login() {
+        let app = actix_test::init_service(
+            App::new()
+                .wrap(SessionMiddleware::new(CookieSessionStore::default(), Key::generate()))
+                .route("/", web::post().to(|_user: EditorUser| async { HttpResponse::Ok().finish() })),
+        )
+        .await;
+
+        let resp = actix_test::call_service(&app, actix_test::TestRequest::post().uri("/").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["code"], "unauthorized");
+    }
+
+    // 以 user_id = 1 登录，然后访问需要登录的接口
+    macro_rules! call_as_user {
+        ($service:expr) => {{
+            let app = actix_test::init_service(
+                App::new()
+                    .wrap(SessionMiddleware::new(CookieSessionStore::default(), Key::generate()))
+                    .app_data(web::Data::new($service))
+                    .route(
+                        "/login",
+                        web::post().to(|session: Session| async move {
+                            session.insert("user_id", 1).unwrap();
+                            HttpResponse::Ok().finish()
+                        }),
+                    )
+                    .route("/me", web::get().to(|_user: CurrentUser| async { HttpResponse::Ok().finish() })),
+            )
+            .await;
+            let resp = actix_test::call_service(&app, actix_test::TestRequest::post().uri("/login").to_request()).await;
+            let cookie = resp.response().cookies().next().unwrap().into_owned();
+            actix_test::call_service(&app, actix_test::TestRequest::get().uri("/me").cookie(cookie).to_request()).await
+        }};
+    }
+
+    #[actix_rt::test]
+    async fn test_session_purged_only_for_missing_user() {
+        // 用户不存在：清除会话并返回 401
+        let store = MemoryStore::new();
+        let resp = call_as_user!(NewsService::new(NewsDao::new(store.clone()), store));
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.response().cookies().any(|c| c.value().is_empty()));
+
+        // 数据库不可用：返回 500，会话保持不变
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy("postgres://postgres@127.0.0.1:1/news")
+            .unwrap();
+        let resp = call_as_user!(NewsService::new(NewsDao::new(NewsRepo::new(pool.clone())), UsersRepo::new(pool)));
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(resp.response().cookies().next().is_none());
+    }
+}
@@ -0,0 +1,57 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::DateTime;
+
+use crate::db::models::{Keyset, NewsCursor};
+
+// 游标令牌：对 "方向.时间(微秒).id" 做 base64url 编码，对客户端不透明
+pub fn encode(keyset: &Keyset) -> String {
+    let (dir, cursor) = match keyset {
+        Keyset::After(c) => ('n', c),
+        Keyset::Before(c) => ('p', c),
+    };
+    let raw = format!("{}.{}.{}", dir, cursor.time.timestamp_micros(), cursor.id);
+    URL_SAFE_NO_PAD.encode(raw)
+}
+
+// 解析游标令牌，格式不正确时返回 None
+pub fn decode(token: &str) -> Option<Keyset> {
+    let bytes = URL_SAFE_NO_PAD.decode(token.trim()).ok()?;
+    let raw = String::from_utf8(bytes).ok()?;
+    let mut parts = raw.splitn(3, '.');
+    let dir = parts.next()?;
+    let time = DateTime::from_timestamp_micros(parts.next()?.parse().ok()?)?;
+    let id = parts.next()?.parse().ok()?;
+
+    let cursor = NewsCursor { time, id };
+    match dir {
+        "n" => Some(Keyset::After(cursor)),
+        "p" => Some(Keyset::Before(cursor)),
+        _ => None,
+    }
+}
+
+// 在 news/cursor.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::news::time::parse_datetime;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = NewsCursor { time: parse_datetime("2025-06-06 13:03:41.987475").unwrap(), id: 42 };
+        for keyset in [Keyset::After(cursor), Keyset::Before(cursor)] {
+            let token = encode(&keyset);
+            assert!(!token.contains('.'));
+            assert_eq!(decode(&token), Some(keyset));
+        }
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        assert!(decode("").is_none());
+        assert!(decode("not a cursor").is_none());
+        assert!(decode(&URL_SAFE_NO_PAD.encode("x.1.2")).is_none());
+        assert!(decode(&URL_SAFE_NO_PAD.encode("n.abc.2")).is_none());
+    }
+}
@@ -1,16 +1,63 @@
-use crate::db::repo::NewsRepo;
-use crate::db::models::NewsModel;
-use crate::news::models::PaginatedNews;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
+use crate::db::store::NewsStore;
+use crate::db::models::{CommentRow, CommentStatus, Keyset, LikedNewsRow, ReactionKind, TrendingNewsRow, NewsCursor, NewsFilter, NewsModel, NewsSearchRow, NewsSort, TimeField, UpsertOutcome};
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use crate::news::cursor;
+use crate::news::models::{CommentNode, NewsDetail, NewsListItem, PaginatedComments, PaginatedNews};
+
+
+// 把按发表时间正序排列的评论组装成讨论串：顶层评论最新的在前，回复按时间正序；
+// 不可见的评论若没有可见的回复则整体去掉，否则只保留位置
+fn build_threads(rows: Vec<CommentRow>) -> Vec<CommentNode> {
+    let mut children: HashMap<Option<i32>, Vec<CommentRow>> = HashMap::new();
+    for row in rows {
+        children.entry(row.parent_id).or_default().push(row);
+    }
+
+    fn build(row: CommentRow, children: &mut HashMap<Option<i32>, Vec<CommentRow>>) -> Option<CommentNode> {
+        let replies: Vec<CommentNode> = children
+            .remove(&Some(row.id))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|reply| build(reply, children))
+            .collect();
+        let visible = row.is_visible();
+        if !visible && replies.is_empty() {
+            return None;
+        }
+        Some(CommentNode {
+            id: row.id,
+            parent_id: row.parent_id,
+            removed: !visible,
+            user_id: visible.then_some(row.user_id),
+            author: visible.then_some(row.author),
+            content: visible.then_some(row.content),
+            status: visible.then_some(row.status),
+            created_at: row.created_at,
+            updated_at: visible.then_some(row.updated_at),
+            replies,
+        })
+    }
+
+    let roots = children.remove(&None).unwrap_or_default();
+    roots
+        .into_iter()
+        .rev()
+        .filter_map(|root| build(root, &mut children))
+        .collect()
+}
 
 #[derive(Clone)]  // 新增 Clone 派生
 pub struct NewsDao {
-    repo: NewsRepo,
+    repo: Arc<dyn NewsStore>, // Postgres（NewsRepo）或内存实现（MemoryStore）
 }
 
 impl NewsDao {
-    pub fn new(repo: NewsRepo) -> Self {
-        Self { repo }
+    pub fn new(repo: impl NewsStore + 'static) -> Self {
+        Self { repo: Arc::new(repo) }
     }
 
     pub async fn create_news(
@@ -19,13 +66,82 @@ impl NewsDao {
         href: &str,
         title: &str,
         content: &str,
+        published_at: Option<DateTime<Utc>>,
     ) -> Result<NewsModel, sqlx::Error> {
         self.repo
-            .create_news(news_type, href, title, content)
+            .create_news(news_type, href, title, content, published_at)
             .await
     }
 
 
+    pub async fn upsert_news(
+        &self,
+        news_type: &str,
+        href: &str,
+        title: &str,
+        content: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<UpsertOutcome, sqlx::Error> {
+        self.repo
+            .upsert_news(news_type, href, title, content, published_at)
+            .await
+    }
+
+    pub async fn update_news(
+        &self,
+        id: i32,
+        news_type: Option<&str>,
+        href: Option<&str>,
+        title: Option<&str>,
+        content: Option<&str>,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<NewsModel>, sqlx::Error> {
+        self.repo
+            .update_news(id, news_type, href, title, content, published_at)
+            .await
+    }
+
+    pub async fn delete_news(&self, id: i32) -> Result<bool, sqlx::Error> {
+        self.repo.soft_delete_news(id).await
+    }
+
+    pub async fn restore_news(&self, id: i32) -> Result<Option<NewsModel>, sqlx::Error> {
+        self.repo.restore_news(id).await
+    }
+
+    // 全文检索，返回当前页结果及命中总数
+    pub async fn search_news(
+        &self,
+        terms: &[String],
+        page: u32,
+        page_size: u32,
+        category: Option<&str>,
+    ) -> Result<(Vec<NewsSearchRow>, i64), sqlx::Error> {
+        let offset = (page.saturating_sub(1) as i64) * (page_size as i64);
+        let rows = self.repo.search_news(terms, category, page_size, offset).await?;
+        let total = self.repo.search_news_count(terms, category).await?;
+        Ok((rows, total))
+    }
+
+    pub async fn reindex_search(&self, batch_size: u32) -> Result<u64, sqlx::Error> {
+        self.repo.reindex_missing_search_vectors(batch_size).await
+    }
+
+    // 查询新闻详情及相关新闻，新闻不存在时返回 None
+    pub async fn get_news_detail(
+        &self,
+        id: i32,
+        related_limit: u32,
+    ) -> Result<Option<NewsDetail>, sqlx::Error> {
+        let news = match self.repo.get_news_by_id(id).await? {
+            Some(news) => news,
+            None => return Ok(None),
+        };
+        let related = self.repo.get_related_news(&news, related_limit).await?;
+
+        Ok(Some(NewsDetail { news, related }))
+    }
+
     /* 
     pub async fn get_paginated(
         &self,
@@ -74,20 +190,267 @@ impl NewsDao {
         &self,
         page: u32,
         page_size: u32,
-        category: Option<&str>,
+        filter: &NewsFilter,
+        sort: NewsSort,
     ) -> Result<PaginatedNews, sqlx::Error> {
-        let offset = (page - 1) * page_size;
-        let news = match category {
-            Some(cat) => self.repo.get_paginated_by_category(page_size, offset as i64, cat).await?,
-            None => self.repo.get_paginated(page, page_size).await?,
-        };
+        let offset = (page.saturating_sub(1) as i64) * (page_size as i64);
+        let news = self.repo.query_news(filter, sort, page_size, offset).await?;
 
-        let total = self.repo.get_news_count(category).await?;
+        let total = self.repo.get_news_count(filter).await?;
         let total_pages = (total as f64 / page_size as f64).ceil() as i64;
 
         Ok(PaginatedNews {
-            news,
-            total_pages,
+            news: news.into_iter().map(NewsListItem::from).collect(),
+            total_pages: Some(total_pages),
+            current_page: Some(page),
+            next_cursor: None,
+            prev_cursor: None,
+        })
+    }
+
+    // 最新的 limit 条新闻（订阅源使用），与列表页同样的查询
+    pub async fn latest_news(&self, filter: &NewsFilter, limit: u32) -> Result<Vec<NewsModel>, sqlx::Error> {
+        self.repo.query_news(filter, NewsSort::Newest, limit, 0).await
+    }
+
+    pub fn stream_news(&self, filter: NewsFilter) -> BoxStream<'static, Result<NewsModel, sqlx::Error>> {
+        self.repo.stream_news(filter)
+    }
+
+    // 游标分页：keyset 为 None 时取第一页
+    pub async fn get_paginated_by_cursor(
+        &self,
+        page_size: u32,
+        filter: &NewsFilter,
+        descending: bool,
+        keyset: Option<&Keyset>,
+    ) -> Result<PaginatedNews, sqlx::Error> {
+        let (news, has_more) = self.repo.query_news_keyset(filter, descending, keyset, page_size).await?;
+
+        let position = |n: &NewsModel| NewsCursor {
+            time: match filter.time_field {
+                TimeField::Published => n.published_at,
+                TimeField::Ingested => n.ingested_at,
+            },
+            id: n.id,
+        };
+        // 沿游标方向：还有数据才给出继续翻页的游标；反方向：不是第一页就给出返回的游标
+        let (has_next, has_prev) = match keyset {
+            None => (has_more, false),
+            Some(Keyset::After(_)) => (has_more, true),
+            Some(Keyset::Before(_)) => (true, has_more),
+        };
+        let next_cursor = news
+            .last()
+            .filter(|_| has_next)
+            .map(|n| cursor::encode(&Keyset::After(position(n))));
+        let prev_cursor = news
+            .first()
+            .filter(|_| has_prev)
+            .map(|n| cursor::encode(&Keyset::Before(position(n))));
+
+        Ok(PaginatedNews {
+            news: news.into_iter().map(NewsListItem::from).collect(),
+            total_pages: None,
+            current_page: None,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+
+    // 收藏新闻：新闻不存在时返回 None，否则返回是否新增
+    pub async fn add_bookmark(&self, user_id: i32, news_id: i32) -> Result<Option<bool>, sqlx::Error> {
+        if self.repo.get_news_by_id(news_id).await?.is_none() {
+            return Ok(None);
+        }
+        self.repo.add_bookmark(user_id, news_id).await.map(Some)
+    }
+
+    pub async fn remove_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, sqlx::Error> {
+        self.repo.remove_bookmark(user_id, news_id).await
+    }
+
+    // 收藏列表，按收藏时间倒序
+    pub async fn get_bookmarks(&self, user_id: i32, page: u32, page_size: u32) -> Result<PaginatedNews, sqlx::Error> {
+        let offset = (page.saturating_sub(1) as i64) * (page_size as i64);
+        let news = self.repo.list_bookmarks(user_id, page_size, offset).await?;
+        let total = self.repo.count_bookmarks(user_id).await?;
+
+        Ok(PaginatedNews {
+            news: news
+                .into_iter()
+                .map(|news| NewsListItem { bookmarked: Some(true), ..NewsListItem::from(news) })
+                .collect(),
+            total_pages: Some((total as f64 / page_size as f64).ceil() as i64),
+            current_page: Some(page),
+            next_cursor: None,
+            prev_cursor: None,
+        })
+    }
+
+    pub async fn bookmarked_ids(&self, user_id: i32, news_ids: &[i32]) -> Result<Vec<i32>, sqlx::Error> {
+        self.repo.bookmarked_ids(user_id, news_ids).await
+    }
+
+    pub async fn increment_view_count(&self, news_id: i32) -> Result<(), sqlx::Error> {
+        self.repo.increment_view_count(news_id).await
+    }
+
+    pub async fn record_read(&self, user_id: i32, news_id: i32) -> Result<(), sqlx::Error> {
+        self.repo.record_read(user_id, news_id).await
+    }
+
+    // 阅读记录，按最近阅读时间倒序
+    pub async fn get_history(&self, user_id: i32, page: u32, page_size: u32) -> Result<PaginatedNews, sqlx::Error> {
+        let offset = (page.saturating_sub(1) as i64) * (page_size as i64);
+        let rows = self.repo.list_history(user_id, page_size, offset).await?;
+        let total = self.repo.count_history(user_id).await?;
+
+        Ok(PaginatedNews {
+            news: rows
+                .into_iter()
+                .map(|row| NewsListItem {
+                    read_at: Some(row.last_read_at),
+                    ..NewsListItem::from(NewsModel {
+                        id: row.id,
+                        news_type: row.news_type,
+                        href: row.href,
+                        title: row.title,
+                        published_at: row.published_at,
+                        ingested_at: row.ingested_at,
+                        content: row.content,
+                    })
+                })
+                .collect(),
+            total_pages: Some((total as f64 / page_size as f64).ceil() as i64),
+            current_page: Some(page),
+            next_cursor: None,
+            prev_cursor: None,
+        })
+    }
+
+    pub async fn remove_history(&self, user_id: i32, news_id: i32) -> Result<bool, sqlx::Error> {
+        self.repo.remove_history(user_id, news_id).await
+    }
+
+    pub async fn clear_history(&self, user_id: i32) -> Result<u64, sqlx::Error> {
+        self.repo.clear_history(user_id).await
+    }
+
+    pub async fn set_reaction(&self, user_id: i32, news_id: i32, reaction: ReactionKind) -> Result<(), sqlx::Error> {
+        self.repo.set_reaction(user_id, news_id, reaction).await
+    }
+
+    pub async fn remove_reaction(&self, user_id: i32, news_id: i32) -> Result<bool, sqlx::Error> {
+        self.repo.remove_reaction(user_id, news_id).await
+    }
+
+    // 按新闻汇总各表态数量
+    pub async fn reaction_counts(
+        &self,
+        news_ids: &[i32],
+    ) -> Result<HashMap<i32, BTreeMap<ReactionKind, i64>>, sqlx::Error> {
+        let mut counts: HashMap<i32, BTreeMap<ReactionKind, i64>> = HashMap::new();
+        for row in self.repo.reaction_counts(news_ids).await? {
+            counts.entry(row.news_id).or_default().insert(row.reaction, row.count);
+        }
+        Ok(counts)
+    }
+
+    pub async fn user_reactions(
+        &self,
+        user_id: i32,
+        news_ids: &[i32],
+    ) -> Result<HashMap<i32, ReactionKind>, sqlx::Error> {
+        let rows = self.repo.user_reactions(user_id, news_ids).await?;
+        Ok(rows.into_iter().map(|row| (row.news_id, row.reaction)).collect())
+    }
+
+    pub async fn most_liked_news(
+        &self,
+        since: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<LikedNewsRow>, sqlx::Error> {
+        self.repo.most_liked_news(since, category, limit).await
+    }
+
+    pub async fn trending_news(
+        &self,
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+        category: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<TrendingNewsRow>, sqlx::Error> {
+        self.repo.trending_news(since, now, category, limit).await
+    }
+
+    // 新闻是否存在且未删除
+    pub async fn news_exists(&self, id: i32) -> Result<bool, sqlx::Error> {
+        Ok(self.repo.get_news_by_id(id).await?.is_some())
+    }
+
+    pub async fn create_comment(
+        &self,
+        news_id: i32,
+        user_id: i32,
+        parent_id: Option<i32>,
+        root_id: Option<i32>,
+        content: &str,
+    ) -> Result<CommentRow, sqlx::Error> {
+        self.repo.create_comment(news_id, user_id, parent_id, root_id, content).await
+    }
+
+    pub async fn get_comment(&self, id: i32) -> Result<Option<CommentRow>, sqlx::Error> {
+        self.repo.get_comment(id).await
+    }
+
+    pub async fn update_comment(&self, id: i32, content: &str) -> Result<Option<CommentRow>, sqlx::Error> {
+        self.repo.update_comment_content(id, content).await
+    }
+
+    pub async fn delete_comment(&self, id: i32) -> Result<bool, sqlx::Error> {
+        self.repo.delete_comment(id).await
+    }
+
+    pub async fn set_comment_status(&self, id: i32, status: CommentStatus) -> Result<Option<CommentRow>, sqlx::Error> {
+        self.repo.set_comment_status(id, status).await
+    }
+
+    // 新闻下的讨论串，按顶层评论分页
+    pub async fn get_comment_threads(
+        &self,
+        news_id: i32,
+        page: u32,
+        page_size: u32,
+    ) -> Result<PaginatedComments<CommentNode>, sqlx::Error> {
+        let offset = (page.saturating_sub(1) as i64) * (page_size as i64);
+        let rows = self.repo.list_comment_threads(news_id, page_size, offset).await?;
+        let total = self.repo.count_comment_threads(news_id).await?;
+
+        Ok(PaginatedComments {
+            comments: build_threads(rows),
+            total,
+            total_pages: (total as f64 / page_size as f64).ceil() as i64,
+            current_page: page,
+        })
+    }
+
+    // 审核队列
+    pub async fn get_comments_by_status(
+        &self,
+        status: CommentStatus,
+        page: u32,
+        page_size: u32,
+    ) -> Result<PaginatedComments<CommentRow>, sqlx::Error> {
+        let offset = (page.saturating_sub(1) as i64) * (page_size as i64);
+        let comments = self.repo.list_comments_by_status(status, page_size, offset).await?;
+        let total = self.repo.count_comments_by_status(status).await?;
+
+        Ok(PaginatedComments {
+            comments,
+            total,
+            total_pages: (total as f64 / page_size as f64).ceil() as i64,
             current_page: page,
         })
     }
@@ -100,22 +463,16 @@ impl NewsDao {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::pool::init_pool;
+    use crate::db::memory::MemoryStore;
 
     #[actix_rt::test]
     async fn test_dao_create_news() {
-        let pool = init_pool().await.unwrap();
-        let repo = NewsRepo::new(pool.clone());
-        let dao = NewsDao::new(repo);
-
-        let mut tx = pool.begin().await.unwrap();
+        let dao = NewsDao::new(MemoryStore::new());
 
         // 测试 DAO 方法
         let result = dao
-            .create_news("tech", "https://dao.test", "DAO Test", "Content")
+            .create_news("tech", &format!("https://dao.test/{}", chrono::Utc::now().timestamp_nanos_opt().unwrap()), "DAO Test", "Content", None)
             .await;
         assert!(result.is_ok());
-
-        tx.rollback().await.unwrap();
     }
 }
\ No newline at end of file
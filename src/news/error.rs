@@ -0,0 +1,176 @@
+use actix_web::{
+    error::{JsonPayloadError, QueryPayloadError},
+    http::{header, StatusCode},
+    HttpRequest, HttpResponse, ResponseError,
+};
+use serde_json::{json, Value};
+use sqlx::Error as SqlxError;
+use thiserror::Error;
+
+// 业务错误；作为 HTTP 响应时统一返回 {"code", "message", "details"}
+#[derive(Debug, Error)]
+pub enum NewsError {
+    #[error("Database error: {0}")]
+    DbError(SqlxError),
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+    #[error("{0} not found")]
+    NotFound(&'static str), // 资源名称，如 "News"、"User"
+    #[error("Conflict: {message}")]
+    Conflict { message: String, constraint: Option<String> },
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Not implemented: {0}")]
+    NotImplemented(String), // 当前服务端配置不支持的功能
+    #[error("Too many requests, retry after {retry_after}s")]
+    RateLimited { retry_after: u64 },
+    #[error("Password hashing error: {0}")]
+    PasswordHashError(String),
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+// 唯一约束冲突转为 409，其余数据库错误保持为 DbError
+impl From<SqlxError> for NewsError {
+    fn from(e: SqlxError) -> Self {
+        if let SqlxError::Database(db) = &e {
+            if db.is_unique_violation() {
+                return NewsError::Conflict {
+                    message: "Resource already exists".into(),
+                    constraint: db.constraint().map(str::to_string),
+                };
+            }
+        }
+        NewsError::DbError(e)
+    }
+}
+
+impl NewsError {
+    // 稳定的错误码，客户端应依据它而不是 message 做判断
+    pub fn code(&self) -> &'static str {
+        match self {
+            NewsError::ValidationError(_) => "validation_error",
+            NewsError::NotFound(_) => "not_found",
+            NewsError::Conflict { .. } => "conflict",
+            NewsError::Unauthorized(_) => "unauthorized",
+            NewsError::Forbidden(_) => "forbidden",
+            NewsError::NotImplemented(_) => "not_implemented",
+            NewsError::RateLimited { .. } => "rate_limited",
+            NewsError::DbError(_) | NewsError::PasswordHashError(_) | NewsError::Internal(_) => "internal_error",
+        }
+    }
+
+    // 返回给客户端的说明；内部错误不暴露数据库等细节
+    fn message(&self) -> String {
+        match self {
+            NewsError::ValidationError(msg)
+            | NewsError::Unauthorized(msg)
+            | NewsError::Forbidden(msg)
+            | NewsError::NotImplemented(msg) => msg.clone(),
+            NewsError::Conflict { message, .. } => message.clone(),
+            NewsError::NotFound(_) | NewsError::RateLimited { .. } => self.to_string(),
+            NewsError::DbError(_) | NewsError::PasswordHashError(_) | NewsError::Internal(_) => {
+                "Internal server error".into()
+            }
+        }
+    }
+
+    fn details(&self) -> Value {
+        match self {
+            NewsError::Conflict { constraint: Some(constraint), .. } => json!({ "constraint": constraint }),
+            NewsError::RateLimited { retry_after } => json!({ "retry_after": retry_after }),
+            _ => Value::Null,
+        }
+    }
+}
+
+impl ResponseError for NewsError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            NewsError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            NewsError::NotFound(_) => StatusCode::NOT_FOUND,
+            NewsError::Conflict { .. } => StatusCode::CONFLICT,
+            NewsError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            NewsError::Forbidden(_) => StatusCode::FORBIDDEN,
+            NewsError::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+            NewsError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            NewsError::DbError(_) | NewsError::PasswordHashError(_) | NewsError::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            log::error!("{}", self);
+        }
+
+        let mut resp = HttpResponse::build(status);
+        if let NewsError::RateLimited { retry_after } = self {
+            resp.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+        resp.json(json!({
+            "code": self.code(),
+            "message": self.message(),
+            "details": self.details(),
+        }))
+    }
+}
+
+// JSON 请求体解析失败时同样返回统一格式；超出大小限制等保持 actix 默认处理
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    match err {
+        JsonPayloadError::Deserialize(e) => NewsError::ValidationError(e.to_string()).into(),
+        JsonPayloadError::ContentType => NewsError::ValidationError("Content type must be application/json".into()).into(),
+        err => err.into(),
+    }
+}
+
+// 查询参数解析失败时返回统一格式
+pub fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    NewsError::ValidationError(err.to_string()).into()
+}
+
+// 在 news/error.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    async fn body_json(e: NewsError) -> (StatusCode, Value) {
+        let resp = e.error_response();
+        let status = resp.status();
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[actix_rt::test]
+    async fn test_error_envelope() {
+        let (status, body) = body_json(NewsError::ValidationError("Title cannot be empty".into())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, json!({ "code": "validation_error", "message": "Title cannot be empty", "details": null }));
+
+        let (status, body) = body_json(NewsError::NotFound("News")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["message"], "News not found");
+
+        let (status, body) = body_json(NewsError::NotImplemented("Session management requires session.store = \"postgres\"".into())).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(body["code"], "not_implemented");
+
+        let (status, body) = body_json(NewsError::RateLimited { retry_after: 30 }).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["details"]["retry_after"], 30);
+    }
+
+    #[actix_rt::test]
+    async fn test_internal_errors_are_not_leaked() {
+        let (status, body) = body_json(NewsError::from(SqlxError::RowNotFound)).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["code"], "internal_error");
+        assert_eq!(body["message"], "Internal server error");
+    }
+}
@@ -0,0 +1,248 @@
+// RSS 2.0 / Atom / JSON Feed 1.1 订阅源：只依赖传入的新闻和频道信息，输出与请求时刻无关，便于计算 ETag
+
+use std::fmt::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
+
+use crate::db::models::NewsModel;
+
+// 订阅源格式，由请求的文件名决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        match name {
+            "rss.xml" => Some(FeedFormat::Rss),
+            "atom.xml" => Some(FeedFormat::Atom),
+            "feed.json" => Some(FeedFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+
+    pub fn render(self, channel: &FeedChannel, items: &[NewsModel]) -> String {
+        match self {
+            FeedFormat::Rss => render_rss(channel, items),
+            FeedFormat::Atom => render_atom(channel, items),
+            FeedFormat::Json => render_json(channel, items),
+        }
+    }
+}
+
+// 订阅源的频道信息
+#[derive(Debug, Clone)]
+pub struct FeedChannel {
+    pub title: String,       // 按分类时为 "站点标题 - 分类"
+    pub description: String,
+    pub site_url: String,    // 站点地址，不带结尾的 /
+    pub self_url: String,    // 订阅源自身的地址
+}
+
+// 转义 XML 特殊字符，并去掉 XML 1.0 不允许出现的控制字符（采集的正文中偶尔会有）
+pub fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// 订阅源的最后修改时间：条目中最近的入库时间
+pub fn last_modified(items: &[NewsModel]) -> Option<DateTime<Utc>> {
+    items.iter().map(|n| n.ingested_at).max()
+}
+
+// 条目的唯一标识：本站新闻详情接口的地址
+fn entry_id(channel: &FeedChannel, news: &NewsModel) -> String {
+    format!("{}/news/{}", channel.site_url, news.id)
+}
+
+fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// RSS 2.0，items 按发布时间倒序
+pub fn render_rss(channel: &FeedChannel, items: &[NewsModel]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n");
+    let _ = writeln!(xml, "<title>{}</title>", escape_xml(&channel.title));
+    let _ = writeln!(xml, "<link>{}/</link>", escape_xml(&channel.site_url));
+    let _ = writeln!(xml, "<description>{}</description>", escape_xml(&channel.description));
+    xml.push_str("<language>zh-CN</language>\n");
+    if let Some(updated) = last_modified(items) {
+        let _ = writeln!(xml, "<lastBuildDate>{}</lastBuildDate>", updated.to_rfc2822());
+    }
+    let _ = writeln!(
+        xml,
+        "<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>",
+        escape_xml(&channel.self_url)
+    );
+    for news in items {
+        xml.push_str("<item>\n");
+        let _ = writeln!(xml, "<title>{}</title>", escape_xml(&news.title));
+        let _ = writeln!(xml, "<link>{}</link>", escape_xml(&news.href));
+        let _ = writeln!(xml, "<guid isPermaLink=\"false\">{}</guid>", escape_xml(&entry_id(channel, news)));
+        let _ = writeln!(xml, "<category>{}</category>", escape_xml(&news.news_type));
+        let _ = writeln!(xml, "<pubDate>{}</pubDate>", news.published_at.to_rfc2822());
+        let _ = writeln!(xml, "<description>{}</description>", escape_xml(&news.content));
+        xml.push_str("</item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+// Atom 1.0，items 按发布时间倒序；没有条目时 updated 取 1970-01-01
+pub fn render_atom(channel: &FeedChannel, items: &[NewsModel]) -> String {
+    let updated = last_modified(items).unwrap_or(DateTime::UNIX_EPOCH);
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"zh-CN\">\n");
+    let _ = writeln!(xml, "<title>{}</title>", escape_xml(&channel.title));
+    let _ = writeln!(xml, "<subtitle>{}</subtitle>", escape_xml(&channel.description));
+    let _ = writeln!(xml, "<id>{}</id>", escape_xml(&channel.self_url));
+    let _ = writeln!(xml, "<link rel=\"self\" href=\"{}\"/>", escape_xml(&channel.self_url));
+    let _ = writeln!(xml, "<link rel=\"alternate\" href=\"{}/\"/>", escape_xml(&channel.site_url));
+    let _ = writeln!(xml, "<updated>{}</updated>", rfc3339(updated));
+    let _ = writeln!(xml, "<author><name>{}</name></author>", escape_xml(&channel.title));
+    for news in items {
+        xml.push_str("<entry>\n");
+        let _ = writeln!(xml, "<title>{}</title>", escape_xml(&news.title));
+        let _ = writeln!(xml, "<id>{}</id>", escape_xml(&entry_id(channel, news)));
+        let _ = writeln!(xml, "<link rel=\"alternate\" href=\"{}\"/>", escape_xml(&news.href));
+        let _ = writeln!(xml, "<published>{}</published>", rfc3339(news.published_at));
+        let _ = writeln!(xml, "<updated>{}</updated>", rfc3339(news.ingested_at));
+        let _ = writeln!(xml, "<category term=\"{}\"/>", escape_xml(&news.news_type));
+        let _ = writeln!(xml, "<summary type=\"text\">{}</summary>", escape_xml(&news.content));
+        xml.push_str("</entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+// JSON Feed 1.1（https://jsonfeed.org/version/1.1），items 按发布时间倒序
+pub fn render_json(channel: &FeedChannel, items: &[NewsModel]) -> String {
+    let items: Vec<_> = items
+        .iter()
+        .map(|news| {
+            json!({
+                "id": entry_id(channel, news),
+                "url": news.href,
+                "title": news.title,
+                "content_text": news.content,
+                "date_published": rfc3339(news.published_at),
+                "date_modified": rfc3339(news.ingested_at),
+                "tags": [news.news_type],
+            })
+        })
+        .collect();
+    let feed = json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": channel.title,
+        "home_page_url": format!("{}/", channel.site_url),
+        "feed_url": channel.self_url,
+        "description": channel.description,
+        "language": "zh-CN",
+        "authors": [{ "name": channel.title }],
+        "items": items,
+    });
+    feed.to_string()
+}
+
+// 在 feed.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn channel() -> FeedChannel {
+        FeedChannel {
+            title: "新闻聚合 - 科技".into(),
+            description: "最新新闻".into(),
+            site_url: "https://news.example.com".into(),
+            self_url: "https://news.example.com/feeds/%E7%A7%91%E6%8A%80/rss.xml".into(),
+        }
+    }
+
+    fn news(id: i32, title: &str) -> NewsModel {
+        NewsModel {
+            id,
+            news_type: "科技".into(),
+            href: format!("https://example.com/a?id={}&from=feed", id),
+            title: title.into(),
+            published_at: Utc.with_ymd_and_hms(2024, 5, 1, 8, 30, 0).unwrap(),
+            ingested_at: Utc.with_ymd_and_hms(2024, 5, 1, 9, id as u32, 0).unwrap(),
+            content: "正文 <b>加粗</b>\u{0}".into(),
+        }
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("「AI」<大模型> & \"芯片\" 'x'"), "「AI」&lt;大模型&gt; &amp; &quot;芯片&quot; &apos;x&apos;");
+        assert_eq!(escape_xml("a\u{1}b\u{b}c\td\n"), "abc\td\n");
+    }
+
+    #[test]
+    fn test_render_rss() {
+        let items = vec![news(2, "华为发布新机 & 鸿蒙"), news(1, "旧闻")];
+        let xml = render_rss(&channel(), &items);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\""));
+        assert!(xml.contains("<title>新闻聚合 - 科技</title>"));
+        assert!(xml.contains("<lastBuildDate>Wed, 1 May 2024 09:02:00 +0000</lastBuildDate>"));
+        assert!(xml.contains("<title>华为发布新机 &amp; 鸿蒙</title>"));
+        assert!(xml.contains("<link>https://example.com/a?id=2&amp;from=feed</link>"));
+        assert!(xml.contains("<guid isPermaLink=\"false\">https://news.example.com/news/2</guid>"));
+        assert!(xml.contains("<pubDate>Wed, 1 May 2024 08:30:00 +0000</pubDate>"));
+        assert!(xml.contains("<description>正文 &lt;b&gt;加粗&lt;/b&gt;</description>"));
+        assert_eq!(xml.matches("<item>").count(), 2);
+        assert!(xml.find("新机").unwrap() < xml.find("旧闻").unwrap());
+
+        let empty = render_rss(&channel(), &[]);
+        assert!(!empty.contains("<lastBuildDate>") && !empty.contains("<item>"));
+    }
+
+    #[test]
+    fn test_render_atom() {
+        let xml = render_atom(&channel(), &[news(3, "标题")]);
+        assert!(xml.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"zh-CN\">"));
+        assert!(xml.contains("<updated>2024-05-01T09:03:00Z</updated>"));
+        assert!(xml.contains("<id>https://news.example.com/news/3</id>"));
+        assert!(xml.contains("<published>2024-05-01T08:30:00Z</published>"));
+        assert!(xml.contains("<category term=\"科技\"/>"));
+        assert!(render_atom(&channel(), &[]).contains("<updated>1970-01-01T00:00:00Z</updated>"));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let feed: serde_json::Value = serde_json::from_str(&render_json(&channel(), &[news(4, "标题 \"引号\"")])).unwrap();
+        assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(feed["home_page_url"], "https://news.example.com/");
+        assert_eq!(feed["items"][0]["id"], "https://news.example.com/news/4");
+        assert_eq!(feed["items"][0]["title"], "标题 \"引号\"");
+        assert_eq!(feed["items"][0]["date_published"], "2024-05-01T08:30:00Z");
+        assert_eq!(feed["items"][0]["tags"], serde_json::json!(["科技"]));
+        assert_eq!(FeedFormat::from_file_name("feed.json"), Some(FeedFormat::Json));
+    }
+}
@@ -0,0 +1,100 @@
+// 检索结果高亮：按字符（而非字节）处理，适配中文文本
+
+// 转义 HTML 特殊字符，防止正文内容被当作标签渲染
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        _ => out.push(c),
+    }
+}
+
+// 标记文本中命中检索词的字符位置（忽略英文大小写）
+fn match_mask(chars: &[char], terms: &[String]) -> Vec<bool> {
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let mut mask = vec![false; chars.len()];
+
+    for term in terms {
+        let term: Vec<char> = term.chars().collect();
+        if term.is_empty() || term.len() > lower.len() {
+            continue;
+        }
+        for start in 0..=(lower.len() - term.len()) {
+            if lower[start..start + term.len()] == term[..] {
+                mask[start..start + term.len()].iter_mut().for_each(|m| *m = true);
+            }
+        }
+    }
+    mask
+}
+
+// 用 <mark> 包裹命中的检索词；max_chars 为 Some 时截取首个命中附近的片段
+pub fn highlight(text: &str, terms: &[String], max_chars: Option<usize>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mask = match_mask(&chars, terms);
+
+    let (start, end) = match max_chars {
+        Some(max) if chars.len() > max => {
+            // 命中位置前保留约四分之一窗口作为上下文
+            let first = mask.iter().position(|m| *m).unwrap_or(0);
+            let start = first.saturating_sub(max / 4).min(chars.len() - max);
+            (start, start + max)
+        }
+        _ => (0, chars.len()),
+    };
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    let mut in_mark = false;
+    for i in start..end {
+        if mask[i] != in_mark {
+            out.push_str(if mask[i] { "<mark>" } else { "</mark>" });
+            in_mark = mask[i];
+        }
+        push_escaped(&mut out, chars[i]);
+    }
+    if in_mark {
+        out.push_str("</mark>");
+    }
+    if end < chars.len() {
+        out.push('…');
+    }
+    out
+}
+
+// 在 news/highlight.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_highlight_chinese_terms() {
+        let out = highlight("货轮太平洋起火", &terms(&["太平洋"]), None);
+        assert_eq!(out, "货轮<mark>太平洋</mark>起火");
+    }
+
+    #[test]
+    fn test_highlight_merges_adjacent_and_escapes() {
+        let out = highlight("<b>Rust 编程</b>", &terms(&["rust", "编程"]), None);
+        assert_eq!(out, "&lt;b&gt;<mark>Rust</mark> <mark>编程</mark>&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_snippet_window() {
+        let text = format!("{}关键词{}", "前".repeat(20), "后".repeat(20));
+        let out = highlight(&text, &terms(&["关键词"]), Some(12));
+        assert_eq!(out, "…前前前<mark>关键词</mark>后后后后后后…");
+    }
+}
@@ -2,6 +2,13 @@ pub mod dao;
 pub mod models;
 pub mod service;
 pub mod routes;
+pub mod password;
+pub mod auth;
+pub mod error;
+pub mod highlight;
+pub mod time;
+pub mod cursor;
+pub mod feed;
 
 // 导出公共接口
 pub use routes::config;
@@ -1,7 +1,11 @@
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize};
 use serde::Serialize;
-use crate::db::models::NewsModel;
+use chrono::{DateTime, Utc};
+use crate::db::models::{CommentStatus, NewsFilter, NewsModel, NewsSort, ReactionKind, TimeField, UpsertOutcome};
+use crate::news::time::deserialize_opt_datetime;
 
 // 创建新闻的请求体
 #[derive(Debug, Deserialize)]
@@ -10,25 +14,306 @@ pub struct NewsCreate {
     pub href: String,
     pub title: String,
     pub content: String,
+    // 原始发布时间；不带时区时按 UTC+8 解释，缺省时使用入库时间
+    #[serde(default, deserialize_with = "deserialize_opt_datetime")]
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+// 部分更新新闻的请求体（PATCH），未提供的字段保持不变
+#[derive(Debug, Deserialize)]
+pub struct NewsPatch {
+    pub news_type: Option<String>,
+    pub href: Option<String>,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_opt_datetime")]
+    pub published_at: Option<DateTime<Utc>>,
 }
 
 // 查询新闻的请求参数
+// category / exclude_category / exclude_ids 均为逗号分隔的列表，如 category=科技,财经
 #[derive(Debug, Deserialize)]
 pub struct NewsQuery {
     pub page: Option<u32>,
     pub page_size: Option<u32>,
     pub category: Option<String>, // 添加这个字段
+    pub exclude_category: Option<String>,
+    pub exclude_ids: Option<String>,
+    pub time_field: Option<TimeField>, // 按 published（默认）或 ingested 排序和过滤
+    #[serde(default, deserialize_with = "deserialize_opt_datetime")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_opt_datetime")]
+    pub to: Option<DateTime<Utc>>,
+    pub sort: Option<NewsSort>, // newest（默认）| oldest | title | popular
+    // 出现该参数时使用游标分页（忽略 page）；空值表示第一页，之后传入返回的 next_cursor/prev_cursor
+    pub cursor: Option<String>,
+    // 为 true 时隐藏当前登录用户已读的新闻；未登录时忽略
+    pub hide_read: Option<bool>,
+}
+
+// 拆分逗号分隔的参数，忽略空项
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl NewsQuery {
+    // 转为仓库层的过滤条件；exclude_ids 中有非数字时返回错误信息
+    pub fn filter(&self) -> Result<NewsFilter, String> {
+        let exclude_ids = split_list(self.exclude_ids.as_deref())
+            .iter()
+            .map(|id| id.parse::<i32>().map_err(|_| format!("invalid id in exclude_ids: {}", id)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(NewsFilter {
+            categories: split_list(self.category.as_deref()),
+            exclude_categories: split_list(self.exclude_category.as_deref()),
+            exclude_ids,
+            unread_by: None,
+            time_field: self.time_field.unwrap_or_default(),
+            from: self.from,
+            to: self.to,
+        })
+    }
+}
+
+
+// 流式导出的过滤参数，含义同新闻列表；不分页，按时间正序
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub category: Option<String>,
+    pub exclude_category: Option<String>,
+    pub time_field: Option<TimeField>,
+    #[serde(default, deserialize_with = "deserialize_opt_datetime")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_opt_datetime")]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl ExportQuery {
+    pub fn filter(&self) -> NewsFilter {
+        NewsFilter {
+            categories: split_list(self.category.as_deref()),
+            exclude_categories: split_list(self.exclude_category.as_deref()),
+            time_field: self.time_field.unwrap_or_default(),
+            from: self.from,
+            to: self.to,
+            ..NewsFilter::default()
+        }
+    }
+}
+
+
+// 全文检索请求参数
+#[derive(Debug, Deserialize)]
+pub struct NewsSearchQuery {
+    pub q: String,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub category: Option<String>,
+}
+
+// 整体设置订阅分类的请求体
+#[derive(Debug, Deserialize)]
+pub struct SubscriptionsUpdate {
+    pub categories: Vec<String>,
+}
+
+// 只有页码参数的分页请求（如收藏列表）
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+// 列表中的单条新闻；登录用户请求时附带是否已收藏
+#[derive(Debug, Serialize)]
+pub struct NewsListItem {
+    #[serde(flatten)]
+    pub news: NewsModel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bookmarked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<DateTime<Utc>>, // 阅读记录中的最近阅读时间
+    pub reactions: BTreeMap<ReactionKind, i64>, // 各表态数量，没有表态的不出现
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub my_reaction: Option<ReactionKind>, // 当前登录用户的表态
+}
+
+impl From<NewsModel> for NewsListItem {
+    fn from(news: NewsModel) -> Self {
+        Self { news, bookmarked: None, read_at: None, reactions: BTreeMap::new(), my_reaction: None }
+    }
+}
+
+// 对新闻表态的请求体
+#[derive(Debug, Deserialize)]
+pub struct ReactionUpdate {
+    pub reaction: ReactionKind,
+}
+
+// 表态后返回的新闻表态概况
+#[derive(Debug, Serialize)]
+pub struct ReactionSummary {
+    pub news_id: i32,
+    pub reaction: Option<ReactionKind>,
+    pub reactions: BTreeMap<ReactionKind, i64>,
+}
+
+// 点赞排行的查询参数
+#[derive(Debug, Deserialize)]
+pub struct MostLikedQuery {
+    pub days: Option<u32>,     // 统计最近几天的点赞，默认 7
+    pub category: Option<String>,
+    pub limit: Option<u32>,    // 每个分类的条数，默认 5
+}
+
+// 点赞排行中的一条
+#[derive(Debug, Serialize)]
+pub struct LikedNews {
+    #[serde(flatten)]
+    pub news: NewsModel,
+    pub likes: i64,
+}
+
+// 热榜的查询参数
+#[derive(Debug, Deserialize)]
+pub struct TrendingQuery {
+    pub category: Option<String>,
+    pub window: Option<String>, // 统计窗口，如 6h、24h、7d，默认 24h
+    pub limit: Option<u32>,     // 默认 10
+}
+
+// 热榜中的一条：窗口内的浏览量、点赞数与热度
+#[derive(Debug, Serialize)]
+pub struct TrendingNews {
+    #[serde(flatten)]
+    pub news: NewsModel,
+    pub views: i64,
+    pub likes: i64,
+    pub score: f64,
 }
 
+#[derive(Debug, Serialize)]
+pub struct TrendingList {
+    pub window: String,
+    pub news: Vec<TrendingNews>,
+}
 
 // 在 news/models.rs 中添加
+// 页码模式返回 total_pages/current_page；游标模式返回 next_cursor/prev_cursor
 #[derive(Debug, Serialize)]
 pub struct PaginatedNews {
-    pub news: Vec<NewsModel>,
+    pub news: Vec<NewsListItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_cursor: Option<String>,
+}
+
+// 单条检索结果：标题与正文片段中的命中词以 <mark> 标出
+#[derive(Debug, Serialize)]
+pub struct NewsSearchResult {
+    #[serde(flatten)]
+    pub news: NewsModel,
+    pub rank: f32,
+    pub title_highlight: String,
+    pub snippet: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedSearchResults {
+    pub results: Vec<NewsSearchResult>,
+    pub total: i64,
     pub total_pages: i64,
     pub current_page: u32,
 }
 
+// 发表评论的请求体；回复时 parent_id 为被回复的评论
+#[derive(Debug, Deserialize)]
+pub struct CommentCreate {
+    pub content: String,
+    pub parent_id: Option<i32>,
+}
+
+// 修改评论的请求体
+#[derive(Debug, Deserialize)]
+pub struct CommentUpdate {
+    pub content: String,
+}
+
+// 审核队列查询参数，status 默认为 pending
+#[derive(Debug, Deserialize)]
+pub struct ModerationQuery {
+    pub status: Option<CommentStatus>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+// 讨论串中的评论及其回复；已删除或被隐藏、但仍有可见回复的评论只保留位置（removed），不返回作者和内容
+#[derive(Debug, Serialize)]
+pub struct CommentNode {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub removed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CommentStatus>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    pub replies: Vec<CommentNode>,
+}
+
+// 评论分页结果：新闻下的讨论串（按顶层评论分页）或审核队列
+#[derive(Debug, Serialize)]
+pub struct PaginatedComments<T> {
+    pub comments: Vec<T>,
+    pub total: i64,
+    pub total_pages: i64,
+    pub current_page: u32,
+}
+
+// 批量写入结果统计
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct IngestSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+}
+
+impl IngestSummary {
+    pub fn record(&mut self, outcome: &UpsertOutcome) {
+        match outcome {
+            UpsertOutcome::Inserted(_) => self.inserted += 1,
+            UpsertOutcome::Updated(_) => self.updated += 1,
+            UpsertOutcome::Unchanged => self.unchanged += 1,
+        }
+    }
+}
+
+// 单篇新闻详情，附带相关新闻
+#[derive(Debug, Serialize)]
+pub struct NewsDetail {
+    pub news: NewsModel,
+    pub related: Vec<NewsModel>,
+}
+
 
 // 在 news/models.rs 底部添加以下测试代码
 #[cfg(test)]
@@ -46,5 +331,46 @@ mod tests {
 
         let data: NewsCreate = serde_json::from_str(json).unwrap();
         assert_eq!(data.news_type, "tech");
+        assert!(data.published_at.is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_news_create_published_at_utc8() {
+        let json = r#"{
+            "news_type": "tech",
+            "href": "https://test.com",
+            "title": "Test",
+            "content": "Content",
+            "published_at": "2025-06-06 08:09:10"
+        }"#;
+
+        let data: NewsCreate = serde_json::from_str(json).unwrap();
+        assert_eq!(data.published_at.unwrap().to_rfc3339(), "2025-06-06T00:09:10+00:00");
+    }
+
+    #[test]
+    fn test_news_query_time_filter() {
+        let query: NewsQuery = serde_urlencoded::from_str("time_field=ingested&from=2025-06-01").unwrap();
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.time_field, TimeField::Ingested);
+        assert_eq!(filter.from.unwrap().to_rfc3339(), "2025-05-31T16:00:00+00:00");
+        assert!(filter.to.is_none());
+    }
+
+    #[test]
+    fn test_news_query_lists_and_sort() {
+        let query: NewsQuery = serde_urlencoded::from_str(
+            "category=%E7%A7%91%E6%8A%80,%20%E8%B4%A2%E7%BB%8F,&exclude_category=tech&exclude_ids=3,5&sort=popular",
+        )
+        .unwrap();
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.categories, vec!["科技", "财经"]);
+        assert_eq!(filter.exclude_categories, vec!["tech"]);
+        assert_eq!(filter.exclude_ids, vec![3, 5]);
+        assert_eq!(query.sort, Some(NewsSort::Popular));
+
+        let query: NewsQuery = serde_urlencoded::from_str("exclude_ids=1,x").unwrap();
+        assert!(query.filter().is_err());
+        assert!(serde_urlencoded::from_str::<NewsQuery>("sort=random").is_err());
+    }
+}
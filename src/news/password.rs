@@ -0,0 +1,62 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use subtle::ConstantTimeEq;
+
+// 密码校验结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum PasswordCheck {
+    Valid,       // 已是 Argon2 哈希且匹配
+    ValidLegacy, // 旧的明文记录且匹配，调用方应重新哈希后回写
+    Invalid,
+}
+
+// 使用 Argon2id 生成 PHC 格式的密码哈希
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+}
+
+// 校验密码：无法解析为 PHC 字符串的记录视为历史明文密码
+pub fn verify_password(password: &str, stored: &str) -> PasswordCheck {
+    match PasswordHash::new(stored) {
+        Ok(parsed) => {
+            if Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok() {
+                PasswordCheck::Valid
+            } else {
+                PasswordCheck::Invalid
+            }
+        }
+        Err(_) => {
+            // 常量时间比较，避免通过响应时间推测明文
+            if bool::from(password.as_bytes().ct_eq(stored.as_bytes())) {
+                PasswordCheck::ValidLegacy
+            } else {
+                PasswordCheck::Invalid
+            }
+        }
+    }
+}
+
+// 在 news/password.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify() {
+        let hash = hash_password("secret").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_ne!(hash, hash_password("secret").unwrap()); // 每次使用不同的盐
+
+        assert_eq!(verify_password("secret", &hash), PasswordCheck::Valid);
+        assert_eq!(verify_password("wrong", &hash), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn test_verify_legacy_plaintext() {
+        assert_eq!(verify_password("secret", "secret"), PasswordCheck::ValidLegacy);
+        assert_eq!(verify_password("wrong", "secret"), PasswordCheck::Invalid);
+    }
+}
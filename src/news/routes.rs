@@ -1,25 +1,76 @@
 // src/routes.rs
 
-use actix_web::{http, web, HttpResponse, Responder, HttpRequest, post}; // 确保引入 post 宏
-use actix_web::App; // App 可能在 main.rs 中使用
+use actix_web::{web, HttpResponse, Responder, HttpRequest};
 use actix_session::Session;
 use crate::{
     // db::repo::{NewsRepo, UsersRepo}, // 这些在 service 层使用，handler 层不直接用 repo
-    db::models::{UserRegister, UserLogin}, // 如果 handler 需要直接处理这些模型
+    db::models::{CommentStatus, UserRegister, UserLogin, UserRoleUpdate}, // 如果 handler 需要直接处理这些模型
     news::{
+        auth::{AdminUser, CurrentUser, EditorUser},
         // dao::NewsDao, // 同上，handler 通过 service 交互
-        service::NewsService,
-        models::{NewsCreate, NewsQuery},
+        error::{json_error_handler, query_error_handler},
+        service::{NewsError, NewsService},
+        models::{NewsCreate, NewsPatch, NewsQuery, NewsSearchQuery, PageQuery, SubscriptionsUpdate},
+        models::{CommentCreate, CommentUpdate, ModerationQuery},
+        models::{ExportQuery, MostLikedQuery, ReactionUpdate, TrendingQuery},
     },
 };
+use crate::config::FeedsConfig;
+use crate::news::feed::{self, FeedChannel, FeedFormat};
+use crate::session::PgSessionStore;
+use actix_web::http::header::{self, EntityTag, Header, HttpDate, IfModifiedSince, IfNoneMatch};
+use actix_web::web::Bytes;
+use chrono::SubsecRound;
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+use actix_session::storage::generate_session_key;
 use serde_json; // 确保引入
 
+// 批量导入请求体大小上限
+const BULK_INGEST_LIMIT: usize = 8 * 1024 * 1024;
+
 // 新闻路由配置
 pub fn config(cfg: &mut web::ServiceConfig) {
+    // 请求体、查询参数解析失败时也返回统一的错误格式
+    cfg.app_data(web::JsonConfig::default().error_handler(json_error_handler))
+       .app_data(web::QueryConfig::default().error_handler(query_error_handler));
     cfg.service(
         web::scope("/news")
            .route("", web::post().to(create_news))
            .route("", web::get().to(list_news))
+           .route("/search", web::get().to(search_news))
+           .route("/feed", web::get().to(news_feed))
+           .route("/most-liked", web::get().to(most_liked_news))
+           .route("/trending", web::get().to(trending_news))
+           .route("/export", web::get().to(export_news))
+           .service(
+                web::resource("/bulk")
+                   .app_data(web::JsonConfig::default().limit(BULK_INGEST_LIMIT).error_handler(json_error_handler))
+                   .route(web::post().to(bulk_ingest_news))
+            )
+           .route("/{id}", web::get().to(get_news))
+           .route("/{id}", web::put().to(update_news))
+           .route("/{id}", web::patch().to(patch_news))
+           .route("/{id}", web::delete().to(delete_news))
+           .route("/{id}/restore", web::post().to(restore_news))
+           .route("/{id}/comments", web::get().to(list_comments))
+           .route("/{id}/comments", web::post().to(add_comment))
+           .route("/{id}/reaction", web::put().to(set_reaction))
+           .route("/{id}/reaction", web::delete().to(remove_reaction))
+    );
+    cfg.service(
+        web::scope("/feeds")
+           .route("/{file}", web::get().to(news_feed_file))
+           .route("/{category}/{file}", web::get().to(category_feed_file))
+    );
+    cfg.service(
+        web::scope("/comments")
+           .route("/moderation", web::get().to(moderation_queue))
+           .route("/{id}", web::patch().to(update_comment))
+           .route("/{id}", web::delete().to(delete_comment))
+           .route("/{id}/approve", web::post().to(approve_comment))
+           .route("/{id}/hide", web::post().to(hide_comment))
     );
     cfg.service(
         web::scope("/user")
@@ -27,6 +78,21 @@ pub fn config(cfg: &mut web::ServiceConfig) {
            .route("/login", web::post().to(login_user))
            .route("/check-login", web::get().to(check_user_login))
            .route("/logout", web::post().to(logout_user)) // <--- 新增登出路由
+           .route("/bookmarks", web::get().to(list_bookmarks))
+           .route("/bookmarks/{news_id}", web::post().to(add_bookmark))
+           .route("/bookmarks/{news_id}", web::delete().to(remove_bookmark))
+           .route("/subscriptions", web::get().to(list_subscriptions))
+           .route("/subscriptions", web::put().to(set_subscriptions))
+           .route("/subscriptions/{category}", web::post().to(add_subscription))
+           .route("/subscriptions/{category}", web::delete().to(remove_subscription))
+           .route("/history", web::get().to(list_history))
+           .route("/history", web::delete().to(clear_history))
+           .route("/history/{news_id}", web::delete().to(remove_history))
+           .route("/sessions", web::get().to(list_sessions))
+           .route("/sessions", web::delete().to(revoke_other_sessions))
+           .route("/sessions/{id}", web::delete().to(revoke_session))
+           .route("/{id}/role", web::put().to(update_user_role))
+           .route("/{id}/sessions", web::delete().to(revoke_user_sessions))
     );
 }
 
@@ -58,18 +124,24 @@ async fn logout_user(session: Session) -> impl Responder {
 // 创建新闻接口
 async fn create_news(
     service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
     req: web::Json<NewsCreate>,
+) -> Result<HttpResponse, NewsError> {
+    log::info!("create_news called by user {}", user.id);
+    let news = service.create_news(req.into_inner()).await?;
+    Ok(HttpResponse::Created().json(news))
+}
+
+
+// 批量导入新闻接口：按 href 去重，返回新增/更新/未变化/失败条数
+async fn bulk_ingest_news(
+    service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
+    req: web::Json<Vec<NewsCreate>>,
 ) -> impl Responder {
-    log::info!("create_news called");
-    match service.create_news(req.into_inner()).await {
-        Ok(news) => HttpResponse::Created().json(news),
-        Err(e) => {
-            log::error!("Error creating news: {}", e); // 添加日志
-            HttpResponse::InternalServerError().json(serde_json::json!({ // 或者更具体的错误码
-                "message": format!("Error: {}", e)
-            }))
-        }
-    }
+    let items = req.into_inner();
+    log::info!("bulk_ingest_news called with {} items by user {}", items.len(), user.id);
+    HttpResponse::Ok().json(service.bulk_ingest(items).await)
 }
 
 
@@ -92,47 +164,364 @@ async fn list_news(
 }
 */
 
-// 修改 list_news 函数
+// 修改 list_news 函数；登录用户请求时每条新闻附带 bookmarked，并支持 hide_read
 async fn list_news(
     service: web::Data<NewsService>,
+    user: Option<CurrentUser>,
     query: web::Query<NewsQuery>,
-) -> impl Responder {
+) -> Result<HttpResponse, NewsError> {
     log::info!("list_news called with query: {:?}", query);
-    
-    match service.get_paginated(query.into_inner()).await {
-        Ok(paginated_news) => HttpResponse::Ok().json(paginated_news),
-        Err(e) => {
-            log::error!("Error listing news: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "message": format!("Error: {}", e)
-            }))
+
+    let paginated_news = service.get_paginated_for(query.into_inner(), user.map(|u| u.id)).await?;
+    Ok(HttpResponse::Ok().json(paginated_news))
+}
+
+
+// 个性化信息流：当前用户订阅分类的新闻
+async fn news_feed(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    query: web::Query<NewsQuery>,
+) -> Result<HttpResponse, NewsError> {
+    log::info!("news_feed called by user {} with query: {:?}", user.id, query);
+
+    let feed = service.get_feed(user.id, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(feed))
+}
+
+// 流式导出新闻（NDJSON，每行一条），边读数据库边写出响应；
+// 中途出错时直接断开连接，客户端据此得知导出不完整
+async fn export_news(
+    service: web::Data<NewsService>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, NewsError> {
+    log::info!("export_news called with query: {:?}", query);
+    let lines = service.export_news(query.into_inner()).map(|row| {
+        let row = row.inspect_err(|e| log::error!("News export aborted: {}", e))?;
+        let mut line = serde_json::to_vec(&row).map_err(|e| NewsError::Internal(e.to_string()))?;
+        line.push(b'\n');
+        Ok::<_, NewsError>(Bytes::from(line))
+    });
+    Ok(HttpResponse::Ok().content_type("application/x-ndjson").streaming(lines))
+}
+
+// 全站订阅源：/feeds/rss.xml、/feeds/atom.xml、/feeds/feed.json
+async fn news_feed_file(
+    req: HttpRequest,
+    service: web::Data<NewsService>,
+    feeds: web::Data<FeedsConfig>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, NewsError> {
+    feed_response(&req, &service, &feeds, None, &path.into_inner()).await
+}
+
+// 按分类的订阅源：/feeds/{category}/rss.xml、atom.xml、feed.json
+async fn category_feed_file(
+    req: HttpRequest,
+    service: web::Data<NewsService>,
+    feeds: web::Data<FeedsConfig>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, NewsError> {
+    let (category, file) = path.into_inner();
+    feed_response(&req, &service, &feeds, Some(category.trim()), &file).await
+}
+
+// 生成订阅源并处理条件请求：ETag 为正文的哈希，Last-Modified 为条目中最近的入库时间；
+// 带 If-None-Match 时只比较 ETag，否则比较 If-Modified-Since
+async fn feed_response(
+    req: &HttpRequest,
+    service: &NewsService,
+    feeds: &FeedsConfig,
+    category: Option<&str>,
+    file: &str,
+) -> Result<HttpResponse, NewsError> {
+    let format = FeedFormat::from_file_name(file).ok_or(NewsError::NotFound("Feed"))?;
+    let category = category.filter(|c| !c.is_empty());
+    let items = service.latest_news(category, feeds.items).await?;
+
+    let site_url = match &feeds.base_url {
+        Some(url) => url.clone(),
+        None => {
+            let info = req.connection_info();
+            format!("{}://{}", info.scheme(), info.host())
+        }
+    };
+    let channel = FeedChannel {
+        title: match category {
+            Some(category) => format!("{} - {}", feeds.title, category),
+            None => feeds.title.clone(),
+        },
+        description: feeds.description.clone(),
+        self_url: format!("{}{}", site_url, req.path()),
+        site_url,
+    };
+    let body = format.render(&channel, &items);
+
+    // 用正文的 SHA-256 作 ETag，不随 Rust 版本或进程变化
+    let etag = EntityTag::new_strong(format!("{:x}", Sha256::digest(body.as_bytes())));
+    let last_modified = feed::last_modified(&items).map(|time| HttpDate::from(SystemTime::from(time.trunc_subsecs(0))));
+
+    let not_modified = if req.headers().contains_key(header::IF_NONE_MATCH) {
+        match IfNoneMatch::parse(req) {
+            Ok(IfNoneMatch::Any) => true,
+            Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+            Err(_) => false,
         }
+    } else {
+        match (IfModifiedSince::parse(req), last_modified) {
+            (Ok(IfModifiedSince(since)), Some(modified)) => modified <= since,
+            _ => false,
+        }
+    };
+
+    let mut resp = if not_modified { HttpResponse::NotModified() } else { HttpResponse::Ok() };
+    resp.insert_header(header::ETag(etag));
+    if let Some(modified) = last_modified {
+        resp.insert_header(header::LastModified(modified));
+    }
+    if not_modified {
+        Ok(resp.finish())
+    } else {
+        Ok(resp.content_type(format.content_type()).body(body))
     }
 }
 
+// 最近若干天点赞最多的新闻，按分类分组
+async fn most_liked_news(
+    service: web::Data<NewsService>,
+    query: web::Query<MostLikedQuery>,
+) -> Result<HttpResponse, NewsError> {
+    let ranking = service.most_liked(query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ranking))
+}
+
+// 热榜：按时间窗口内的浏览量和点赞数计算热度
+async fn trending_news(
+    service: web::Data<NewsService>,
+    query: web::Query<TrendingQuery>,
+) -> Result<HttpResponse, NewsError> {
+    let trending = service.trending(query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(trending))
+}
+
+
+// 全文检索接口
+async fn search_news(
+    service: web::Data<NewsService>,
+    query: web::Query<NewsSearchQuery>,
+) -> Result<HttpResponse, NewsError> {
+    log::info!("search_news called with query: {:?}", query);
+
+    let results = service.search_news(query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(results))
+}
+
+
+// 新闻详情接口；登录用户访问时写入阅读记录
+async fn get_news(
+    service: web::Data<NewsService>,
+    user: Option<CurrentUser>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("get_news called with id: {}", id);
+
+    let detail = service.get_news_detail_for(id, user.map(|u| u.id)).await?;
+    Ok(HttpResponse::Ok().json(detail))
+}
+
+
+// 整体更新新闻接口
+async fn update_news(
+    service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
+    path: web::Path<i32>,
+    req: web::Json<NewsCreate>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("update_news called with id: {} by user {}", id, user.id);
+    let news = service.update_news(id, req.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(news))
+}
+
+// 部分更新新闻接口
+async fn patch_news(
+    service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
+    path: web::Path<i32>,
+    req: web::Json<NewsPatch>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("patch_news called with id: {} by user {}", id, user.id);
+    let news = service.patch_news(id, req.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(news))
+}
+
+// 删除新闻接口（软删除）
+async fn delete_news(
+    service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("delete_news called with id: {} by user {}", id, user.id);
+    service.delete_news(id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// 恢复已删除新闻接口
+async fn restore_news(
+    service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("restore_news called with id: {} by user {}", id, user.id);
+    let news = service.restore_news(id).await?;
+    Ok(HttpResponse::Ok().json(news))
+}
+
+
+// 新闻评论列表：按顶层评论分页，附带回复
+async fn list_comments(
+    service: web::Data<NewsService>,
+    path: web::Path<i32>,
+    query: web::Query<PageQuery>,
+) -> Result<HttpResponse, NewsError> {
+    let comments = service.list_comments(path.into_inner(), query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(comments))
+}
+
+// 发表评论或回复
+async fn add_comment(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+    req: web::Json<CommentCreate>,
+) -> Result<HttpResponse, NewsError> {
+    let news_id = path.into_inner();
+    log::info!("add_comment called with news {} by user {}", news_id, user.id);
+    let comment = service.add_comment(news_id, user.id, req.into_inner()).await?;
+    Ok(HttpResponse::Created().json(comment))
+}
+
+// 对新闻点赞或表态，已有表态时替换
+async fn set_reaction(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+    req: web::Json<ReactionUpdate>,
+) -> Result<HttpResponse, NewsError> {
+    let news_id = path.into_inner();
+    log::info!("set_reaction called with news {} by user {}", news_id, user.id);
+    let summary = service.react(user.id, news_id, req.into_inner().reaction).await?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+// 撤销表态
+async fn remove_reaction(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let news_id = path.into_inner();
+    log::info!("remove_reaction called with news {} by user {}", news_id, user.id);
+    service.unreact(user.id, news_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// 修改自己的评论
+async fn update_comment(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+    req: web::Json<CommentUpdate>,
+) -> Result<HttpResponse, NewsError> {
+    let comment = service.update_comment(path.into_inner(), user.id, req.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(comment))
+}
+
+// 删除评论（作者本人或编辑）
+async fn delete_comment(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("delete_comment called with id: {} by user {}", id, user.id);
+    service.delete_comment(id, user.id, user.role).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// 评论审核队列（编辑）
+async fn moderation_queue(
+    service: web::Data<NewsService>,
+    EditorUser(_): EditorUser,
+    query: web::Query<ModerationQuery>,
+) -> Result<HttpResponse, NewsError> {
+    let queue = service.moderation_queue(query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(queue))
+}
+
+// 审核通过评论（编辑）
+async fn approve_comment(
+    service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("approve_comment called with id: {} by user {}", id, user.id);
+    let comment = service.moderate_comment(id, CommentStatus::Approved).await?;
+    Ok(HttpResponse::Ok().json(comment))
+}
+
+// 隐藏评论（编辑）
+async fn hide_comment(
+    service: web::Data<NewsService>,
+    EditorUser(user): EditorUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let id = path.into_inner();
+    log::info!("hide_comment called with id: {} by user {}", id, user.id);
+    let comment = service.moderate_comment(id, CommentStatus::Hidden).await?;
+    Ok(HttpResponse::Ok().json(comment))
+}
+
+
+// 修改用户角色接口（仅管理员）
+async fn update_user_role(
+    service: web::Data<NewsService>,
+    AdminUser(admin): AdminUser,
+    path: web::Path<i32>,
+    req: web::Json<UserRoleUpdate>,
+) -> Result<HttpResponse, NewsError> {
+    let user_id = path.into_inner();
+    let role = req.into_inner().role;
+    log::info!("update_user_role called for user {} by admin {}", user_id, admin.id);
+    let user = service.update_user_role(user_id, role).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": user.id,
+        "name": user.name,
+        "email": user.email,
+        "role": user.role
+    })))
+}
+
 
 // 用户注册接口
 async fn register_user(
     service: web::Data<NewsService>,
     req: web::Json<UserRegister>,
-) -> impl Responder {
+) -> Result<HttpResponse, NewsError> {
     log::info!("register_user called");
-    match service.register_user(req.into_inner()).await {
-        Ok(user) => HttpResponse::Created().json(serde_json::json!({
-            "message": "User registered successfully",
-            "user": user // 你可能只想返回部分用户信息，而不是整个模型（包含密码哈希等）
-        })),
-        Err(e) => { // 根据错误类型返回不同状态码
-            log::warn!("Registration failed: {}", e);
-            // 假设 NewsError::ValidationError 来自 service 层
-            // if let NewsError::ValidationError(msg) = e { // 如果你的 NewsError 定义了 ValidationError
-            //     return HttpResponse::BadRequest().json(serde_json::json!({"message": msg}));
-            // }
-            HttpResponse::BadRequest().json(serde_json::json!({
-                "message": format!("Registration failed: {}", e) // 这里的e是NewsError
-            }))
-        }
-    }
+    // 校验失败返回 400，邮箱已注册返回 409
+    let user = service.register_user(req.into_inner()).await.inspect_err(|e| {
+        log::warn!("Registration failed: {}", e);
+    })?;
+    Ok(HttpResponse::Created().json(serde_json::json!({
+        "message": "User registered successfully",
+        "user": user // 你可能只想返回部分用户信息，而不是整个模型（包含密码哈希等）
+    })))
 }
 
 // 用户登录接口
@@ -140,36 +529,32 @@ async fn login_user(
     service: web::Data<NewsService>,
     req: web::Json<UserLogin>,
     session: Session,
-) -> impl Responder {
+) -> Result<HttpResponse, NewsError> {
     log::info!("login_user called");
-    match service.login_user(req.into_inner()).await {
-        Ok(user) => {
-            // 在session中存储用户ID或其他必要信息
-            // 注意：unwrap() 在生产代码中应谨慎使用，最好处理Error
-            if let Err(e) = session.insert("user_id", user.id) {
-                log::error!("Failed to insert user_id into session: {}", e);
-                return HttpResponse::InternalServerError().json(serde_json::json!({
-                    "message": "Session error during login"
-                }));
-            }
-            // 你可能还想存储用户名等，方便check-login时直接使用或显示
-            // if let Err(e) = session.insert("username", user.name.clone()) { ... }
-
-            log::info!("User {} logged in successfully, session id stored.", user.id);
-            HttpResponse::Ok().json(serde_json::json!({
-                "message": "User logged in successfully",
-                // 出于安全考虑，通常不应在登录响应中返回完整的用户信息（尤其是密码）
-                // 可以只返回部分信息或一个token（如果使用JWT）
-                "user": { "id": user.id, "name": user.name, "email": user.email }
-            }))
-        }
-        Err(e) => {
-            log::warn!("Login failed: {}", e);
-            HttpResponse::Unauthorized().json(serde_json::json!({
-                "message": format!("Login failed: {}", e)
-            }))
-        }
-    }
+    let user = service.login_user(req.into_inner()).await.inspect_err(|e| {
+        log::warn!("Login failed: {}", e);
+    })?;
+
+    // 登录后更换会话 key，防止会话固定攻击
+    session.renew();
+    // 在session中存储用户ID或其他必要信息；sid 用于在会话列表中识别当前会话
+    session
+        .insert("user_id", user.id)
+        .map_err(|e| NewsError::Internal(format!("Failed to insert user_id into session: {}", e)))?;
+    let sid: String = generate_session_key().into();
+    session
+        .insert("sid", sid)
+        .map_err(|e| NewsError::Internal(format!("Failed to insert sid into session: {}", e)))?;
+    // 你可能还想存储用户名等，方便check-login时直接使用或显示
+    // if let Err(e) = session.insert("username", user.name.clone()) { ... }
+
+    log::info!("User {} logged in successfully, session id stored.", user.id);
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "User logged in successfully",
+        // 出于安全考虑，通常不应在登录响应中返回完整的用户信息（尤其是密码）
+        // 可以只返回部分信息或一个token（如果使用JWT）
+        "user": { "id": user.id, "name": user.name, "email": user.email, "role": user.role }
+    })))
 }
 
 // 检查用户登录状态接口
@@ -177,45 +562,654 @@ async fn check_user_login(
     service: web::Data<NewsService>, // 仍然需要 service 来获取用户信息
     session: Session,
     _req: HttpRequest, // HttpRequest 可能不需要，除非你要检查请求头等
-) -> impl Responder {
+) -> Result<HttpResponse, NewsError> {
     log::info!("check_user_login called");
     // 尝试从 session 获取 user_id
-    match session.get::<i32>("user_id") {
-        Ok(Some(user_id)) => {
-            log::info!("User ID {} found in session.", user_id);
-            // 根据 user_id 从数据库获取用户信息
-            // (你的 NewsService 已经有 get_user_by_id 方法)
-            match service.get_user_by_id(user_id).await {
-                Ok(user_model) => {
-                    // 出于安全，不返回密码等敏感信息
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "id": user_model.id,
-                        "name": user_model.name,
-                        "email": user_model.email
-                        // 不应包含 password
-                    }))
-                }
-                Err(e) => {
-                    log::error!("Failed to get user by ID {} from service: {}", user_id, e);
-                    // 用户ID在session中，但数据库中找不到，可能是数据不一致或用户已被删除
-                    session.purge(); // 清除无效的session
-                    HttpResponse::Unauthorized().json(serde_json::json!({
-                        "message": "User not found or session invalid"
-                    }))
-                }
-            }
+    let user_id = session
+        .get::<i32>("user_id")
+        .map_err(|e| NewsError::Internal(format!("Error getting user_id from session: {}", e)))?
+        .ok_or_else(|| NewsError::Unauthorized("User not logged in".into()))?; // 更明确的未登录消息
+    log::info!("User ID {} found in session.", user_id);
+
+    // 根据 user_id 从数据库获取用户信息
+    match service.get_user_by_id(user_id).await {
+        Ok(user_model) => {
+            // 出于安全，不返回密码等敏感信息
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "id": user_model.id,
+                "name": user_model.name,
+                "email": user_model.email,
+                "role": user_model.role
+                // 不应包含 password
+            })))
         }
-        Ok(None) => {
-            log::info!("No user_id found in session.");
-            HttpResponse::Unauthorized().json(serde_json::json!({
-                "message": "User not logged in" // 更明确的未登录消息
-            }))
+        Err(NewsError::NotFound(_)) => {
+            // 用户ID在session中，但数据库中找不到，可能是数据不一致或用户已被删除
+            session.purge(); // 清除无效的session
+            Err(NewsError::Unauthorized("User not found or session invalid".into()))
         }
-        Err(e) => {
-            log::error!("Error getting user_id from session: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "message": "Session error"
-            }))
+        Err(e) => Err(e),
+    }
+}
+
+// 当前用户的收藏列表
+async fn list_bookmarks(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    query: web::Query<PageQuery>,
+) -> Result<HttpResponse, NewsError> {
+    let bookmarks = service.get_bookmarks(user.id, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(bookmarks))
+}
+
+// 收藏新闻：新增返回 201，已收藏返回 200
+async fn add_bookmark(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let news_id = path.into_inner();
+    log::info!("add_bookmark called with news {} by user {}", news_id, user.id);
+    let body = serde_json::json!({ "news_id": news_id, "bookmarked": true });
+    if service.add_bookmark(user.id, news_id).await? {
+        Ok(HttpResponse::Created().json(body))
+    } else {
+        Ok(HttpResponse::Ok().json(body))
+    }
+}
+
+// 取消收藏
+async fn remove_bookmark(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let news_id = path.into_inner();
+    log::info!("remove_bookmark called with news {} by user {}", news_id, user.id);
+    service.remove_bookmark(user.id, news_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+
+// 当前用户订阅的分类
+async fn list_subscriptions(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+) -> Result<HttpResponse, NewsError> {
+    let categories = service.get_subscriptions(user.id).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "categories": categories })))
+}
+
+// 整体设置订阅的分类
+async fn set_subscriptions(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    req: web::Json<SubscriptionsUpdate>,
+) -> Result<HttpResponse, NewsError> {
+    let categories = service.set_subscriptions(user.id, req.into_inner()).await?;
+    log::info!("User {} subscribed to {} categories", user.id, categories.len());
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "categories": categories })))
+}
+
+// 订阅单个分类：新增返回 201，已订阅返回 200
+async fn add_subscription(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, NewsError> {
+    let category = path.into_inner();
+    let body = serde_json::json!({ "category": category.trim(), "subscribed": true });
+    if service.add_subscription(user.id, &category).await? {
+        Ok(HttpResponse::Created().json(body))
+    } else {
+        Ok(HttpResponse::Ok().json(body))
+    }
+}
+
+// 取消订阅单个分类
+async fn remove_subscription(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, NewsError> {
+    service.remove_subscription(user.id, &path.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// 当前用户的阅读记录，最近阅读的在前（"继续阅读"）
+async fn list_history(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    query: web::Query<PageQuery>,
+) -> Result<HttpResponse, NewsError> {
+    let history = service.get_history(user.id, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(history))
+}
+
+// 删除单条阅读记录
+async fn remove_history(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let news_id = path.into_inner();
+    service.remove_history(user.id, news_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// 清空阅读记录
+async fn clear_history(
+    service: web::Data<NewsService>,
+    user: CurrentUser,
+) -> Result<HttpResponse, NewsError> {
+    let cleared = service.clear_history(user.id).await?;
+    log::info!("User {} cleared {} history entries", user.id, cleared);
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "cleared": cleared })))
+}
+
+
+// 会话管理需要服务端会话存储（session.store = "postgres"）
+fn session_store(store: Option<web::Data<PgSessionStore>>) -> Result<web::Data<PgSessionStore>, NewsError> {
+    store.ok_or_else(|| NewsError::NotImplemented("Session management requires session.store = \"postgres\"".into()))
+}
+
+fn current_sid(session: &Session) -> Option<String> {
+    session.get::<String>("sid").ok().flatten()
+}
+
+// 列出当前用户的登录会话
+async fn list_sessions(
+    store: Option<web::Data<PgSessionStore>>,
+    user: CurrentUser,
+    session: Session,
+) -> Result<HttpResponse, NewsError> {
+    let store = session_store(store)?;
+    let sid = current_sid(&session);
+    let sessions: Vec<serde_json::Value> = store
+        .list_user_sessions(user.id)
+        .await?
+        .into_iter()
+        .map(|s| {
+            let current = s.sid.is_some() && s.sid == sid;
+            serde_json::json!({
+                "id": s.id,
+                "created_at": s.created_at,
+                "updated_at": s.updated_at,
+                "expires_at": s.expires_at,
+                "current": current
+            })
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+// 撤销当前用户的某个会话；撤销的是当前会话时等同于登出
+async fn revoke_session(
+    store: Option<web::Data<PgSessionStore>>,
+    user: CurrentUser,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, NewsError> {
+    let store = session_store(store)?;
+    let id = path.into_inner();
+    let sid = current_sid(&session);
+    let is_current = store
+        .list_user_sessions(user.id)
+        .await?
+        .iter()
+        .any(|s| s.id == id && s.sid.is_some() && s.sid == sid);
+    if !store.revoke(user.id, id).await? {
+        return Err(NewsError::NotFound("Session"));
+    }
+    log::info!("User {} revoked session {}", user.id, id);
+    if is_current {
+        session.purge();
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// 撤销当前用户除当前会话外的所有会话（"退出其他设备"）
+async fn revoke_other_sessions(
+    store: Option<web::Data<PgSessionStore>>,
+    user: CurrentUser,
+    session: Session,
+) -> Result<HttpResponse, NewsError> {
+    let store = session_store(store)?;
+    let sid = current_sid(&session);
+    let revoked = store.revoke_all(user.id, sid.as_deref()).await?;
+    log::info!("User {} revoked {} other sessions", user.id, revoked);
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "revoked": revoked })))
+}
+
+// 撤销指定用户的全部会话（仅管理员）
+async fn revoke_user_sessions(
+    store: Option<web::Data<PgSessionStore>>,
+    AdminUser(admin): AdminUser,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NewsError> {
+    let store = session_store(store)?;
+    let user_id = path.into_inner();
+    let revoked = store.revoke_all(user_id, None).await?;
+    log::info!("Admin {} revoked {} sessions of user {}", admin.id, revoked, user_id);
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "revoked": revoked })))
+}
+
+
+// 在 news/routes.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory::MemoryStore;
+    use crate::db::models::{ReactionKind, UserRole};
+    use crate::db::store::{NewsStore, UserStore};
+    use crate::news::dao::NewsDao;
+    use actix_http::Request;
+    use actix_session::{storage::CookieSessionStore, SessionMiddleware};
+    use actix_web::body::MessageBody;
+    use actix_web::dev::{Service, ServiceResponse};
+    use actix_web::{cookie::{Cookie, Key}, http::StatusCode, test as actix_test, App};
+    use serde_json::{json, Value};
+
+    // 基于内存存储的完整应用，不需要数据库
+    macro_rules! test_app {
+        ($store:expr) => {
+            actix_test::init_service(
+                App::new()
+                    .wrap(SessionMiddleware::new(CookieSessionStore::default(), Key::generate()))
+                    .app_data(web::Data::new(NewsService::new(NewsDao::new($store.clone()), $store.clone())))
+                    .app_data(web::Data::new(FeedsConfig::default()))
+                    .configure(config),
+            )
+            .await
+        };
+    }
+
+    // 注册（密码均为 secret）并登录，返回会话 cookie
+    async fn login_cookie<S, B>(app: &S, email: &str) -> Cookie<'static>
+    where
+        S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+        B: MessageBody,
+    {
+        let req = actix_test::TestRequest::post()
+            .uri("/user/register")
+            .set_json(json!({ "name": "Reader", "email": email, "password": "secret" }))
+            .to_request();
+        actix_test::call_service(app, req).await;
+        let req = actix_test::TestRequest::post()
+            .uri("/user/login")
+            .set_json(json!({ "email": email, "password": "secret" }))
+            .to_request();
+        let resp = actix_test::call_service(app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        resp.response().cookies().next().unwrap().into_owned()
+    }
+
+    #[actix_rt::test]
+    async fn test_news_routes_with_memory_store() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+
+        // 注册并登录，拿到会话 cookie
+        let req = actix_test::TestRequest::post()
+            .uri("/user/register")
+            .set_json(json!({ "name": "Editor", "email": "editor@example.com", "password": "secret" }))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+        let user = store.get_user_by_email("editor@example.com").await.unwrap();
+        let login = || {
+            actix_test::TestRequest::post()
+                .uri("/user/login")
+                .set_json(json!({ "email": "editor@example.com", "password": "secret" }))
+                .to_request()
+        };
+        let resp = actix_test::call_service(&app, login()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let cookie: Cookie<'static> = resp.response().cookies().next().unwrap().into_owned();
+
+        // 普通读者不能发布新闻
+        let news = json!({ "news_type": "tech", "href": "https://routes.test/1", "title": "Hello", "content": "World" });
+        let req = actix_test::TestRequest::post().uri("/news").cookie(cookie.clone()).set_json(&news).to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // 直接在存储中提升角色后可以发布
+        store.update_role(user.id, UserRole::Editor).await.unwrap();
+        let req = actix_test::TestRequest::post().uri("/news").cookie(cookie.clone()).set_json(&news).to_request();
+        let created: Value = actix_test::call_and_read_body_json(&app, req).await;
+        let id = created["id"].as_i64().unwrap();
+
+        let req = actix_test::TestRequest::get().uri("/news?category=tech").to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(list["news"].as_array().unwrap().len(), 1);
+        assert_eq!(list["total_pages"], 1);
+
+        let req = actix_test::TestRequest::get().uri(&format!("/news/{}", id)).to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // 重复的 href 返回统一格式的冲突错误
+        let req = actix_test::TestRequest::post().uri("/news").cookie(cookie).set_json(&news).to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let body: Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["code"], "conflict");
+    }
+
+    #[actix_rt::test]
+    async fn test_update_news_validation() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+        let news = store.create_news("tech", "https://routes.test/1", "Hello", "World", None).await.unwrap();
+
+        let cookie = login_cookie(&app, "editor@example.com").await;
+        let user = store.get_user_by_email("editor@example.com").await.unwrap();
+        store.update_role(user.id, UserRole::Editor).await.unwrap();
+
+        let uri = format!("/news/{}", news.id);
+        let put = |body: Value| {
+            actix_test::TestRequest::put().uri(&uri).cookie(cookie.clone()).set_json(body).to_request()
+        };
+        let patch = |body: Value| {
+            actix_test::TestRequest::patch().uri(&uri).cookie(cookie.clone()).set_json(body).to_request()
+        };
+
+        // PUT 与 POST 一样要求字段非空
+        let full = json!({ "news_type": "tech", "href": "https://routes.test/2", "title": "Hi", "content": "There" });
+        for field in ["news_type", "href", "title", "content"] {
+            let mut body = full.clone();
+            body[field] = json!("");
+            let resp = actix_test::call_service(&app, put(body)).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "empty {}", field);
+            let body: Value = actix_test::read_body_json(resp).await;
+            assert_eq!(body["code"], "validation_error");
+        }
+        let updated: Value = actix_test::call_and_read_body_json(&app, put(full)).await;
+        assert_eq!(updated["href"], "https://routes.test/2");
+
+        // PATCH 中提供的字段不能为空
+        for field in ["news_type", "href", "title", "content"] {
+            let resp = actix_test::call_service(&app, patch(json!({ field: "" }))).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "empty {}", field);
         }
+        let updated: Value = actix_test::call_and_read_body_json(&app, patch(json!({ "title": "Patched" }))).await;
+        assert_eq!((&updated["title"], &updated["href"]), (&json!("Patched"), &json!("https://routes.test/2")));
+    }
+
+    #[actix_rt::test]
+    async fn test_bookmark_routes() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+        let a = store.create_news("tech", "https://routes.test/a", "A", "Content", None).await.unwrap();
+        let b = store.create_news("tech", "https://routes.test/b", "B", "Content", None).await.unwrap();
+
+        let cookie = login_cookie(&app, "reader@example.com").await;
+
+        // 未登录不能收藏
+        let req = actix_test::TestRequest::post().uri(&format!("/user/bookmarks/{}", a.id)).to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+
+        let bookmark = |id: i32| {
+            actix_test::TestRequest::post().uri(&format!("/user/bookmarks/{}", id)).cookie(cookie.clone()).to_request()
+        };
+        assert_eq!(actix_test::call_service(&app, bookmark(a.id)).await.status(), StatusCode::CREATED);
+        assert_eq!(actix_test::call_service(&app, bookmark(a.id)).await.status(), StatusCode::OK);
+        assert_eq!(actix_test::call_service(&app, bookmark(999)).await.status(), StatusCode::NOT_FOUND);
+
+        let req = actix_test::TestRequest::get().uri("/user/bookmarks").cookie(cookie.clone()).to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(list["news"].as_array().unwrap().len(), 1);
+        assert_eq!(list["news"][0]["id"], a.id);
+        assert_eq!(list["news"][0]["bookmarked"], true);
+        assert_eq!(list["total_pages"], 1);
+
+        // 登录后新闻列表带 bookmarked，未登录时不带
+        let req = actix_test::TestRequest::get().uri("/news?sort=oldest").cookie(cookie.clone()).to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        let flags: Vec<(i64, bool)> = list["news"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| (n["id"].as_i64().unwrap(), n["bookmarked"].as_bool().unwrap()))
+            .collect();
+        assert_eq!(flags, vec![(a.id as i64, true), (b.id as i64, false)]);
+        let req = actix_test::TestRequest::get().uri("/news").to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert!(list["news"][0].get("bookmarked").is_none());
+
+        // 每页条数超出范围
+        let req = actix_test::TestRequest::get().uri("/user/bookmarks?page_size=101").cookie(cookie.clone()).to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+        let req = actix_test::TestRequest::get().uri("/news?page_size=0").to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+        let unbookmark = || {
+            actix_test::TestRequest::delete().uri(&format!("/user/bookmarks/{}", a.id)).cookie(cookie.clone()).to_request()
+        };
+        assert_eq!(actix_test::call_service(&app, unbookmark()).await.status(), StatusCode::NO_CONTENT);
+        assert_eq!(actix_test::call_service(&app, unbookmark()).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn test_reaction_routes() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+        let a = store.create_news("tech", "https://routes.test/a", "A", "Content", None).await.unwrap();
+        let b = store.create_news("sports", "https://routes.test/b", "B", "Content", None).await.unwrap();
+        // 另一个用户先点赞 a
+        let other = store.create_user("Other", "other@example.com", "x").await.unwrap();
+        store.set_reaction(other.id, a.id, ReactionKind::Like).await.unwrap();
+
+        let cookie = login_cookie(&app, "reader@example.com").await;
+
+        let react = |id: i32, reaction: &str| {
+            actix_test::TestRequest::put()
+                .uri(&format!("/news/{}/reaction", id))
+                .cookie(cookie.clone())
+                .set_json(json!({ "reaction": reaction }))
+                .to_request()
+        };
+        // 未登录不能表态
+        let req = actix_test::TestRequest::put()
+            .uri(&format!("/news/{}/reaction", a.id))
+            .set_json(json!({ "reaction": "like" }))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(actix_test::call_service(&app, react(a.id, "meh")).await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(actix_test::call_service(&app, react(999, "like")).await.status(), StatusCode::NOT_FOUND);
+
+        let summary: Value = actix_test::call_and_read_body_json(&app, react(a.id, "wow")).await;
+        assert_eq!(summary, json!({ "news_id": a.id, "reaction": "wow", "reactions": { "like": 1, "wow": 1 } }));
+        // 重复表态替换原有表态，不重复计数
+        let summary: Value = actix_test::call_and_read_body_json(&app, react(a.id, "like")).await;
+        assert_eq!(summary["reactions"], json!({ "like": 2 }));
+
+        // 列表中每条新闻带表态数量，登录用户另带自己的表态
+        let req = actix_test::TestRequest::get().uri("/news?sort=oldest").cookie(cookie.clone()).to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(list["news"][0]["reactions"], json!({ "like": 2 }));
+        assert_eq!(list["news"][0]["my_reaction"], "like");
+        assert_eq!(list["news"][1]["reactions"], json!({}));
+        assert!(list["news"][1].get("my_reaction").is_none());
+        let req = actix_test::TestRequest::get().uri("/news?sort=oldest").to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(list["news"][0]["reactions"], json!({ "like": 2 }));
+        assert!(list["news"][0].get("my_reaction").is_none());
+
+        // 点赞排行按分类分组
+        actix_test::call_service(&app, react(b.id, "like")).await;
+        let req = actix_test::TestRequest::get().uri("/news/most-liked?days=1").to_request();
+        let ranking: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(ranking["tech"][0]["id"], a.id);
+        assert_eq!(ranking["tech"][0]["likes"], 2);
+        assert_eq!(ranking["sports"][0]["likes"], 1);
+        let req = actix_test::TestRequest::get().uri("/news/most-liked?category=sports").to_request();
+        let ranking: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(ranking.as_object().unwrap().keys().collect::<Vec<_>>(), vec!["sports"]);
+        let req = actix_test::TestRequest::get().uri("/news/most-liked?days=0").to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+        let unreact = || {
+            actix_test::TestRequest::delete().uri(&format!("/news/{}/reaction", a.id)).cookie(cookie.clone()).to_request()
+        };
+        assert_eq!(actix_test::call_service(&app, unreact()).await.status(), StatusCode::NO_CONTENT);
+        assert_eq!(actix_test::call_service(&app, unreact()).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn test_feed_routes() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+        store.create_news("科技", "https://routes.test/a?x=1&y=2", "芯片 & <AI>", "正文", None).await.unwrap();
+        store.create_news("体育", "https://routes.test/b", "比赛", "正文", None).await.unwrap();
+
+        let req = actix_test::TestRequest::get().uri("/feeds/rss.xml").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "application/rss+xml; charset=utf-8");
+        let etag = resp.headers().get(header::ETAG).unwrap().clone();
+        let last_modified = resp.headers().get(header::LAST_MODIFIED).unwrap().clone();
+        let body = String::from_utf8(actix_test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains("<title>芯片 &amp; &lt;AI&gt;</title>"));
+        assert!(body.contains("<link>https://routes.test/a?x=1&amp;y=2</link>"));
+        assert!(body.contains("<title>比赛</title>"));
+        assert!(body.contains("<atom:link href=\"http://localhost:8080/feeds/rss.xml\""));
+        // ETag 为正文的 SHA-256
+        assert_eq!(etag.to_str().unwrap(), format!("\"{:x}\"", Sha256::digest(body.as_bytes())));
+
+        // 条件请求
+        let req = actix_test::TestRequest::get()
+            .uri("/feeds/rss.xml")
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(resp.headers().get(header::ETAG).unwrap(), &etag);
+        let req = actix_test::TestRequest::get()
+            .uri("/feeds/rss.xml")
+            .insert_header((header::IF_MODIFIED_SINCE, last_modified))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::NOT_MODIFIED);
+        let req = actix_test::TestRequest::get()
+            .uri("/feeds/rss.xml")
+            .insert_header((header::IF_NONE_MATCH, "\"stale\""))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        // 按分类的 Atom 订阅源，ETag 与全站的不同
+        let req = actix_test::TestRequest::get().uri("/feeds/%E7%A7%91%E6%8A%80/atom.xml").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "application/atom+xml; charset=utf-8");
+        assert_ne!(resp.headers().get(header::ETAG).unwrap(), &etag);
+        let body = String::from_utf8(actix_test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains("<title>新闻聚合 - 科技</title>"));
+        assert!(body.contains("芯片") && !body.contains("比赛"));
+
+        let req = actix_test::TestRequest::get().uri("/feeds/feed.json").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "application/feed+json; charset=utf-8");
+        let feed: Value = actix_test::read_body_json(resp).await;
+        assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(feed["feed_url"], "http://localhost:8080/feeds/feed.json");
+        assert_eq!(feed["items"].as_array().unwrap().len(), 2);
+
+        let req = actix_test::TestRequest::get().uri("/feeds/feed.txt").to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn test_export_routes() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+        let b = store.create_news("tech", "https://routes.test/b", "B", "Content", None).await.unwrap();
+        let a = store
+            .create_news("tech", "https://routes.test/a", "A", "Content", Some(b.published_at - chrono::Duration::hours(1)))
+            .await
+            .unwrap();
+        let c = store.create_news("sports", "https://routes.test/c", "C", "Content", None).await.unwrap();
+
+        let req = actix_test::TestRequest::get().uri("/news/export").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "application/x-ndjson");
+        let body = String::from_utf8(actix_test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.ends_with('\n'));
+        let ids: Vec<i64> = body
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, vec![a.id as i64, b.id as i64, c.id as i64]);
+
+        let req = actix_test::TestRequest::get().uri("/news/export?exclude_category=tech").to_request();
+        let body = actix_test::call_and_read_body(&app, req).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert_eq!(body.lines().count(), 1);
+        assert!(body.contains("\"title\":\"C\""));
+    }
+
+    #[actix_rt::test]
+    async fn test_history_routes() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+        let a = store.create_news("tech", "https://routes.test/a", "A", "Content", None).await.unwrap();
+        let b = store.create_news("tech", "https://routes.test/b", "B", "Content", None).await.unwrap();
+
+        let cookie = login_cookie(&app, "reader@example.com").await;
+
+        // 未登录访问详情不记录
+        let req = actix_test::TestRequest::get().uri(&format!("/news/{}", b.id)).to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::OK);
+        let req = actix_test::TestRequest::get().uri(&format!("/news/{}", a.id)).cookie(cookie.clone()).to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = actix_test::TestRequest::get().uri("/user/history").cookie(cookie.clone()).to_request();
+        let history: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(history["news"].as_array().unwrap().len(), 1);
+        assert_eq!(history["news"][0]["id"], a.id);
+        assert!(history["news"][0]["read_at"].is_string());
+
+        // hide_read 只对登录用户生效
+        let ids = |list: &Value| list["news"].as_array().unwrap().iter().map(|n| n["id"].as_i64().unwrap()).collect::<Vec<_>>();
+        let req = actix_test::TestRequest::get().uri("/news?hide_read=true").cookie(cookie.clone()).to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(ids(&list), vec![b.id as i64]);
+        let req = actix_test::TestRequest::get().uri("/news?hide_read=true").to_request();
+        let list: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(ids(&list).len(), 2);
+
+        let req = actix_test::TestRequest::delete().uri(&format!("/user/history/{}", b.id)).cookie(cookie.clone()).to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+        let req = actix_test::TestRequest::delete().uri("/user/history").cookie(cookie.clone()).to_request();
+        let body: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body, json!({ "cleared": 1 }));
     }
-}
\ No newline at end of file
+
+    #[actix_rt::test]
+    async fn test_error_envelope_from_routes() {
+        let store = MemoryStore::new();
+        let app = test_app!(store);
+
+        let req = actix_test::TestRequest::get().uri("/news/42").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body: Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["code"], "not_found");
+
+        let req = actix_test::TestRequest::get().uri("/news?page=abc").to_request();
+        let body: Value = actix_test::read_body_json(actix_test::call_service(&app, req).await).await;
+        assert_eq!(body["code"], "validation_error");
+
+        let req = actix_test::TestRequest::get().uri("/user/check-login").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // cookie 会话存储下不支持会话管理，属于服务端配置而非请求错误
+        let cookie = login_cookie(&app, "reader@example.com").await;
+        let req = actix_test::TestRequest::get().uri("/user/sessions").cookie(cookie).to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
+        let body: Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["code"], "not_implemented");
+    }
+}
@@ -1,33 +1,142 @@
-use crate::db::repo::{NewsRepo, UsersRepo};
-use crate::db::models::{NewsModel, UserModel, UserRegister, UserLogin};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+
+use crate::db::store::UserStore;
+use crate::db::models::{CommentRow, CommentStatus, NewsFilter, NewsModel, ReactionKind, NewsSort, UpsertOutcome, UserModel, UserRegister, UserLogin, UserRole};
 use crate::news::dao::NewsDao;
-use crate::news::models::{NewsCreate, NewsQuery};
-use crate::news::models::PaginatedNews;
-use thiserror::Error;
-use sqlx::Error as SqlxError;
-use chrono::Utc;
-
-#[derive(Debug, Error)]
-pub enum NewsError {
-    #[error("Database error: {0}")]
-    DbError(#[from] SqlxError),
-    #[error("Validation error: {0}")]
-    ValidationError(String),
-    #[error("User not found")]
-    UserNotFound,
-    #[error("Invalid password")]
-    InvalidPassword,
+use crate::news::models::{NewsCreate, NewsPatch, NewsQuery};
+use crate::news::models::{IngestSummary, NewsDetail, NewsSearchQuery, NewsSearchResult, PaginatedNews, PaginatedSearchResults};
+use crate::news::models::{NewsListItem, PageQuery, SubscriptionsUpdate};
+use crate::news::models::{LikedNews, MostLikedQuery, ReactionSummary};
+use crate::news::models::{ExportQuery, TrendingList, TrendingNews, TrendingQuery};
+use futures_util::stream::{BoxStream, StreamExt};
+use crate::news::models::{CommentCreate, CommentNode, CommentUpdate, ModerationQuery, PaginatedComments};
+use crate::news::cursor;
+use crate::news::highlight::highlight;
+use crate::db::tokenizer::tokenize;
+use crate::news::password::{hash_password, verify_password, PasswordCheck};
+
+// 错误类型定义在 news::error，这里重新导出以保持原有路径
+pub use crate::news::error::NewsError;
+
+// 详情页附带的相关新闻条数
+const RELATED_NEWS_LIMIT: u32 = 5;
+// 检索结果正文片段的最大字符数
+const SEARCH_SNIPPET_CHARS: usize = 120;
+// 每个用户最多订阅的分类数与分类名的最大字符数
+const MAX_SUBSCRIPTIONS: usize = 50;
+const MAX_CATEGORY_CHARS: usize = 32;
+// 评论内容的最大字符数
+const MAX_COMMENT_CHARS: usize = 2000;
+// 点赞排行：默认统计天数与上限、每个分类的默认条数与上限
+const MOST_LIKED_DEFAULT_DAYS: u32 = 7;
+const MOST_LIKED_MAX_DAYS: u32 = 365;
+const MOST_LIKED_DEFAULT_LIMIT: u32 = 5;
+const MOST_LIKED_MAX_LIMIT: u32 = 50;
+// 热榜：默认统计窗口与窗口上限（小时）、默认条数与上限
+const TRENDING_DEFAULT_WINDOW: &str = "24h";
+const TRENDING_MAX_WINDOW_HOURS: i64 = 30 * 24;
+const TRENDING_DEFAULT_LIMIT: u32 = 10;
+const TRENDING_MAX_LIMIT: u32 = 50;
+// 分页接口每页条数的上限
+const MAX_PAGE_SIZE: u32 = 100;
+
+// 新闻字段校验（创建与批量写入共用）
+fn validate_news(data: &NewsCreate) -> Result<(), NewsError> {
+    if data.news_type.is_empty() {
+        return Err(NewsError::ValidationError("News type cannot be empty".into()));
+    }
+    if data.href.is_empty() {
+        return Err(NewsError::ValidationError("Href cannot be empty".into()));
+    }
+    if data.title.is_empty() {
+        return Err(NewsError::ValidationError("Title cannot be empty".into()));
+    }
+    if data.content.is_empty() {
+        return Err(NewsError::ValidationError("Content cannot be empty".into()));
+    }
+    Ok(())
+}
+
+// 分页参数（所有分页接口共用）：page 从 1 开始，0 按 1 处理；page_size 须在 1..=MAX_PAGE_SIZE 之间
+fn page_params(page: Option<u32>, page_size: Option<u32>, default_page_size: u32) -> Result<(u32, u32), NewsError> {
+    let page_size = page_size.unwrap_or(default_page_size);
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(NewsError::ValidationError(format!(
+            "page_size must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    Ok((page.unwrap_or(1).max(1), page_size))
+}
+
+// 热榜统计窗口：数字加单位 h（小时）或 d（天），如 6h、7d
+fn parse_window(window: &str) -> Result<Duration, NewsError> {
+    let invalid = || {
+        NewsError::ValidationError(format!(
+            "window must look like 24h or 7d and be at most {} days: {}",
+            TRENDING_MAX_WINDOW_HOURS / 24,
+            window
+        ))
+    };
+    let hours = if let Some(amount) = window.strip_suffix('h') {
+        amount.parse::<i64>().map_err(|_| invalid())?
+    } else if let Some(amount) = window.strip_suffix('d') {
+        let days = amount.parse::<i64>().map_err(|_| invalid())?;
+        days.checked_mul(24).ok_or_else(invalid)?
+    } else {
+        return Err(invalid());
+    };
+    if hours <= 0 || hours > TRENDING_MAX_WINDOW_HOURS {
+        return Err(invalid());
+    }
+    Ok(Duration::hours(hours))
+}
+
+// 订阅的分类名：去掉首尾空白；不能为空或包含逗号（信息流按逗号拼接分类查询）
+fn normalize_category(category: &str) -> Result<String, NewsError> {
+    let category = category.trim();
+    if category.is_empty() {
+        return Err(NewsError::ValidationError("Category cannot be empty".into()));
+    }
+    if category.contains(',') {
+        return Err(NewsError::ValidationError(format!("Category cannot contain ',': {}", category)));
+    }
+    if category.chars().count() > MAX_CATEGORY_CHARS {
+        return Err(NewsError::ValidationError(format!(
+            "Category must be at most {} characters",
+            MAX_CATEGORY_CHARS
+        )));
+    }
+    Ok(category.to_string())
+}
+
+// 评论内容：去掉首尾空白后不能为空，且不超过 MAX_COMMENT_CHARS
+fn validate_comment(content: &str) -> Result<&str, NewsError> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Err(NewsError::ValidationError("Comment cannot be empty".into()));
+    }
+    if content.chars().count() > MAX_COMMENT_CHARS {
+        return Err(NewsError::ValidationError(format!(
+            "Comment must be at most {} characters",
+            MAX_COMMENT_CHARS
+        )));
+    }
+    Ok(content)
 }
 
 #[derive(Clone)]  // 新增 Clone 派生
 pub struct NewsService {
     news_dao: NewsDao,
-    users_repo: UsersRepo,
+    users_repo: Arc<dyn UserStore>, // Postgres（UsersRepo）或内存实现（MemoryStore）
 }
 
 impl NewsService {
-    pub fn new(news_dao: NewsDao, users_repo: UsersRepo) -> Self {
-        Self { news_dao, users_repo }
+    pub fn new(news_dao: NewsDao, users_repo: impl UserStore + 'static) -> Self {
+        Self { news_dao, users_repo: Arc::new(users_repo) }
     }
 
     // 创建新闻（含基础验证）
@@ -35,18 +144,113 @@ impl NewsService {
         &self,
         data: NewsCreate,
     ) -> Result<NewsModel, NewsError> {
-        // 简单字段校验
-        if data.title.is_empty() {
+        validate_news(&data)?;
+
+        self.news_dao
+           .create_news(&data.news_type, &data.href, &data.title, &data.content, data.published_at)
+           .await
+           .map_err(NewsError::from)
+    }
+
+    // 按 href 幂等写入新闻（采集与批量导入使用）
+    pub async fn upsert_news(
+        &self,
+        data: NewsCreate,
+    ) -> Result<UpsertOutcome, NewsError> {
+        validate_news(&data)?;
+
+        self.news_dao
+            .upsert_news(&data.news_type, &data.href, &data.title, &data.content, data.published_at)
+            .await
+            .map_err(NewsError::from)
+    }
+
+    // 批量写入新闻，单条失败只计数不中断
+    pub async fn bulk_ingest(&self, items: Vec<NewsCreate>) -> IngestSummary {
+        let mut summary = IngestSummary::default();
+        for item in items {
+            let href = item.href.clone();
+            match self.upsert_news(item).await {
+                Ok(outcome) => summary.record(&outcome),
+                Err(e) => {
+                    log::warn!("Failed to ingest news {}: {}", href, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+        summary
+    }
+
+    // 整体更新新闻（PUT），校验规则与创建相同
+    pub async fn update_news(
+        &self,
+        id: i32,
+        data: NewsCreate,
+    ) -> Result<NewsModel, NewsError> {
+        validate_news(&data)?;
+
+        self.patch_news(id, NewsPatch {
+            news_type: Some(data.news_type),
+            href: Some(data.href),
+            title: Some(data.title),
+            content: Some(data.content),
+            published_at: data.published_at,
+        })
+        .await
+    }
+
+    // 部分更新新闻（PATCH）
+    pub async fn patch_news(
+        &self,
+        id: i32,
+        data: NewsPatch,
+    ) -> Result<NewsModel, NewsError> {
+        if data.news_type.is_none() && data.href.is_none() && data.title.is_none() && data.content.is_none()
+            && data.published_at.is_none()
+        {
+            return Err(NewsError::ValidationError("No fields to update".into()));
+        }
+        if data.news_type.as_deref() == Some("") {
+            return Err(NewsError::ValidationError("News type cannot be empty".into()));
+        }
+        if data.href.as_deref() == Some("") {
+            return Err(NewsError::ValidationError("Href cannot be empty".into()));
+        }
+        if data.title.as_deref() == Some("") {
             return Err(NewsError::ValidationError("Title cannot be empty".into()));
         }
-        if data.content.is_empty() {
+        if data.content.as_deref() == Some("") {
             return Err(NewsError::ValidationError("Content cannot be empty".into()));
         }
 
         self.news_dao
-           .create_news(&data.news_type, &data.href, &data.title, &data.content)
-           .await
-           .map_err(NewsError::DbError)
+            .update_news(
+                id,
+                data.news_type.as_deref(),
+                data.href.as_deref(),
+                data.title.as_deref(),
+                data.content.as_deref(),
+                data.published_at,
+            )
+            .await?
+            .ok_or(NewsError::NotFound("News"))
+    }
+
+    // 软删除新闻
+    pub async fn delete_news(&self, id: i32) -> Result<(), NewsError> {
+        if self.news_dao.delete_news(id).await? {
+            Ok(())
+        } else {
+            Err(NewsError::NotFound("News"))
+        }
+    }
+
+    // 恢复已删除的新闻
+    pub async fn restore_news(&self, id: i32) -> Result<NewsModel, NewsError> {
+        self.news_dao
+            .restore_news(id)
+            .await?
+            .ok_or(NewsError::NotFound("News"))
     }
 
 
@@ -62,7 +266,7 @@ impl NewsService {
         self.news_dao
            .get_paginated(page, page_size)
            .await
-           .map_err(NewsError::DbError)
+           .map_err(NewsError::from)
     }
     */
 
@@ -72,16 +276,412 @@ impl NewsService {
         &self,
         query: NewsQuery,
     ) -> Result<PaginatedNews, NewsError> {
-        let page = query.page.unwrap_or(1);
-        let page_size = query.page_size.unwrap_or(10);
-        let category = query.category.as_deref();
-        
+        self.get_paginated_for(query, None).await
+    }
+
+    // 登录用户查询新闻列表：支持 hide_read，结果附带 bookmarked
+    pub async fn get_paginated_for(
+        &self,
+        query: NewsQuery,
+        user_id: Option<i32>,
+    ) -> Result<PaginatedNews, NewsError> {
+        let mut page = self.query_news_page(&query, user_id).await?;
+        if let Some(user_id) = user_id {
+            self.mark_bookmarked(user_id, &mut page.news).await?;
+        }
+        self.fill_reactions(&mut page.news, user_id).await?;
+        Ok(page)
+    }
+
+    async fn query_news_page(
+        &self,
+        query: &NewsQuery,
+        user_id: Option<i32>,
+    ) -> Result<PaginatedNews, NewsError> {
+        let (page, page_size) = page_params(query.page, query.page_size, 10)?;
+        let mut filter = query.filter().map_err(NewsError::ValidationError)?;
+        if query.hide_read == Some(true) {
+            filter.unread_by = user_id;
+        }
+        if let (Some(from), Some(to)) = (filter.from, filter.to) {
+            if from >= to {
+                return Err(NewsError::ValidationError("from must be earlier than to".into()));
+            }
+        }
+
+        let sort = query.sort.unwrap_or_default();
+
+        if let Some(token) = query.cursor.as_deref() {
+            // 游标只支持按时间排序，title/popular 的排序键会在翻页过程中变化或重复
+            let descending = match sort {
+                NewsSort::Newest => true,
+                NewsSort::Oldest => false,
+                _ => {
+                    return Err(NewsError::ValidationError(
+                        "cursor pagination supports sort=newest or sort=oldest only".into(),
+                    ))
+                }
+            };
+            let keyset = match token.trim() {
+                "" => None,
+                token => Some(cursor::decode(token).ok_or_else(|| NewsError::ValidationError("Invalid cursor".into()))?),
+            };
+            return self
+                .news_dao
+                .get_paginated_by_cursor(page_size, &filter, descending, keyset.as_ref())
+                .await
+                .map_err(NewsError::from);
+        }
+
         self.news_dao
-            .get_paginated_with_count(page, page_size, category)
+            .get_paginated_with_count(page, page_size, &filter, sort)
             .await
-            .map_err(NewsError::DbError)
+            .map_err(NewsError::from)
+    }
+
+
+    // 为登录用户标记列表中已收藏的新闻
+    async fn mark_bookmarked(&self, user_id: i32, items: &mut [NewsListItem]) -> Result<(), NewsError> {
+        let ids: Vec<i32> = items.iter().map(|item| item.news.id).collect();
+        let bookmarked = self.news_dao.bookmarked_ids(user_id, &ids).await?;
+        for item in items {
+            item.bookmarked = Some(bookmarked.contains(&item.news.id));
+        }
+        Ok(())
+    }
+
+    // 填充列表中各新闻的表态数量，登录用户另外带上自己的表态
+    async fn fill_reactions(&self, items: &mut [NewsListItem], user_id: Option<i32>) -> Result<(), NewsError> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<i32> = items.iter().map(|item| item.news.id).collect();
+        let mut counts = self.news_dao.reaction_counts(&ids).await?;
+        let mine = match user_id {
+            Some(user_id) => self.news_dao.user_reactions(user_id, &ids).await?,
+            None => Default::default(),
+        };
+        for item in items {
+            item.reactions = counts.remove(&item.news.id).unwrap_or_default();
+            item.my_reaction = mine.get(&item.news.id).copied();
+        }
+        Ok(())
+    }
+
+    // 对新闻表态；每个用户对每条新闻只保留一个表态，重复表态会替换原有的
+    pub async fn react(&self, user_id: i32, news_id: i32, reaction: ReactionKind) -> Result<ReactionSummary, NewsError> {
+        if !self.news_dao.news_exists(news_id).await? {
+            return Err(NewsError::NotFound("News"));
+        }
+        self.news_dao.set_reaction(user_id, news_id, reaction).await?;
+        let reactions = self.news_dao.reaction_counts(&[news_id]).await?.remove(&news_id).unwrap_or_default();
+        Ok(ReactionSummary { news_id, reaction: Some(reaction), reactions })
+    }
+
+    // 撤销表态
+    pub async fn unreact(&self, user_id: i32, news_id: i32) -> Result<(), NewsError> {
+        if self.news_dao.remove_reaction(user_id, news_id).await? {
+            Ok(())
+        } else {
+            Err(NewsError::NotFound("Reaction"))
+        }
+    }
+
+    // 最近若干天点赞最多的新闻，按分类分组
+    pub async fn most_liked(&self, query: MostLikedQuery) -> Result<BTreeMap<String, Vec<LikedNews>>, NewsError> {
+        let days = query.days.unwrap_or(MOST_LIKED_DEFAULT_DAYS);
+        if days == 0 || days > MOST_LIKED_MAX_DAYS {
+            return Err(NewsError::ValidationError(format!(
+                "days must be between 1 and {}",
+                MOST_LIKED_MAX_DAYS
+            )));
+        }
+        let limit = query.limit.unwrap_or(MOST_LIKED_DEFAULT_LIMIT);
+        if limit == 0 || limit > MOST_LIKED_MAX_LIMIT {
+            return Err(NewsError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MOST_LIKED_MAX_LIMIT
+            )));
+        }
+        let category = query.category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+        let since = Utc::now() - Duration::days(days as i64);
+
+        let rows = self.news_dao.most_liked_news(since, category, limit).await?;
+        let mut grouped: BTreeMap<String, Vec<LikedNews>> = BTreeMap::new();
+        for row in rows {
+            grouped.entry(row.news_type.clone()).or_default().push(LikedNews {
+                news: NewsModel {
+                    id: row.id,
+                    news_type: row.news_type,
+                    href: row.href,
+                    title: row.title,
+                    published_at: row.published_at,
+                    ingested_at: row.ingested_at,
+                    content: row.content,
+                },
+                likes: row.likes,
+            });
+        }
+        Ok(grouped)
     }
 
+    // 订阅源条目：最新发布的新闻，可按分类过滤
+    pub async fn latest_news(&self, category: Option<&str>, limit: u32) -> Result<Vec<NewsModel>, NewsError> {
+        let filter = NewsFilter {
+            categories: category.map(|c| vec![c.to_string()]).unwrap_or_default(),
+            ..NewsFilter::default()
+        };
+        Ok(self.news_dao.latest_news(&filter, limit).await?)
+    }
+
+    // 导出符合条件的全部新闻，逐条返回，不一次性载入内存
+    pub fn export_news(&self, query: ExportQuery) -> BoxStream<'static, Result<NewsModel, NewsError>> {
+        self.news_dao.stream_news(query.filter()).map(|row| row.map_err(NewsError::from)).boxed()
+    }
+
+    // 热榜：统计窗口内的浏览量和点赞数，按发布时间衰减后的热度排序
+    pub async fn trending(&self, query: TrendingQuery) -> Result<TrendingList, NewsError> {
+        let window = query
+            .window
+            .as_deref()
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+            .unwrap_or(TRENDING_DEFAULT_WINDOW)
+            .to_lowercase();
+        let duration = parse_window(&window)?;
+        let limit = query.limit.unwrap_or(TRENDING_DEFAULT_LIMIT);
+        if limit == 0 || limit > TRENDING_MAX_LIMIT {
+            return Err(NewsError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                TRENDING_MAX_LIMIT
+            )));
+        }
+        let category = query.category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+        let now = Utc::now();
+
+        let rows = self.news_dao.trending_news(now - duration, now, category, limit).await?;
+        let news = rows
+            .into_iter()
+            .map(|row| TrendingNews {
+                news: NewsModel {
+                    id: row.id,
+                    news_type: row.news_type,
+                    href: row.href,
+                    title: row.title,
+                    published_at: row.published_at,
+                    ingested_at: row.ingested_at,
+                    content: row.content,
+                },
+                views: row.views,
+                likes: row.likes,
+                score: row.score,
+            })
+            .collect();
+        Ok(TrendingList { window, news })
+    }
+
+    // 收藏新闻，返回是否新增；重复收藏不报错
+    pub async fn add_bookmark(&self, user_id: i32, news_id: i32) -> Result<bool, NewsError> {
+        self.news_dao
+            .add_bookmark(user_id, news_id)
+            .await?
+            .ok_or(NewsError::NotFound("News"))
+    }
+
+    // 取消收藏
+    pub async fn remove_bookmark(&self, user_id: i32, news_id: i32) -> Result<(), NewsError> {
+        if self.news_dao.remove_bookmark(user_id, news_id).await? {
+            Ok(())
+        } else {
+            Err(NewsError::NotFound("Bookmark"))
+        }
+    }
+
+    // 用户的收藏列表
+    pub async fn get_bookmarks(&self, user_id: i32, query: PageQuery) -> Result<PaginatedNews, NewsError> {
+        let (page, page_size) = page_params(query.page, query.page_size, 10)?;
+        let mut bookmarks = self.news_dao.get_bookmarks(user_id, page, page_size).await?;
+        self.fill_reactions(&mut bookmarks.news, Some(user_id)).await?;
+        Ok(bookmarks)
+    }
+
+    // 用户的阅读记录，最近阅读的在前
+    pub async fn get_history(&self, user_id: i32, query: PageQuery) -> Result<PaginatedNews, NewsError> {
+        let (page, page_size) = page_params(query.page, query.page_size, 10)?;
+        let mut history = self.news_dao.get_history(user_id, page, page_size).await?;
+        self.fill_reactions(&mut history.news, Some(user_id)).await?;
+        Ok(history)
+    }
+
+    // 删除单条阅读记录
+    pub async fn remove_history(&self, user_id: i32, news_id: i32) -> Result<(), NewsError> {
+        if self.news_dao.remove_history(user_id, news_id).await? {
+            Ok(())
+        } else {
+            Err(NewsError::NotFound("History"))
+        }
+    }
+
+    // 清空阅读记录，返回删除条数
+    pub async fn clear_history(&self, user_id: i32) -> Result<u64, NewsError> {
+        Ok(self.news_dao.clear_history(user_id).await?)
+    }
+
+
+    // 全文检索新闻（中文分词）
+    pub async fn search_news(
+        &self,
+        query: NewsSearchQuery,
+    ) -> Result<PaginatedSearchResults, NewsError> {
+        let terms = tokenize(&query.q);
+        if terms.is_empty() {
+            return Err(NewsError::ValidationError("Search query cannot be empty".into()));
+        }
+        let (page, page_size) = page_params(query.page, query.page_size, 10)?;
+
+        let (rows, total) = self.news_dao
+            .search_news(&terms, page, page_size, query.category.as_deref())
+            .await?;
+
+        let results = rows
+            .into_iter()
+            .map(|row| NewsSearchResult {
+                title_highlight: highlight(&row.title, &terms, None),
+                snippet: highlight(&row.content, &terms, Some(SEARCH_SNIPPET_CHARS)),
+                rank: row.rank,
+                news: NewsModel {
+                    id: row.id,
+                    news_type: row.news_type,
+                    href: row.href,
+                    title: row.title,
+                    published_at: row.published_at,
+                    ingested_at: row.ingested_at,
+                    content: row.content,
+                },
+            })
+            .collect();
+
+        Ok(PaginatedSearchResults {
+            results,
+            total,
+            total_pages: (total as f64 / page_size as f64).ceil() as i64,
+            current_page: page,
+        })
+    }
+
+    // 为缺少检索向量的历史数据补建索引
+    pub async fn rebuild_search_index(&self) -> Result<u64, NewsError> {
+        Ok(self.news_dao.reindex_search(500).await?)
+    }
+
+    // 查询单篇新闻详情
+    pub async fn get_news_detail(&self, id: i32) -> Result<NewsDetail, NewsError> {
+        self.get_news_detail_for(id, None).await
+    }
+
+    // 查看详情时累计浏览量，登录用户还写入阅读记录；写入失败只记日志，不影响返回详情
+    pub async fn get_news_detail_for(&self, id: i32, user_id: Option<i32>) -> Result<NewsDetail, NewsError> {
+        let detail = self.news_dao
+            .get_news_detail(id, RELATED_NEWS_LIMIT)
+            .await?
+            .ok_or(NewsError::NotFound("News"))?;
+        if let Err(e) = self.news_dao.increment_view_count(id).await {
+            log::warn!("Failed to count view of news {}: {}", id, e);
+        }
+        if let Some(user_id) = user_id {
+            if let Err(e) = self.news_dao.record_read(user_id, id).await {
+                log::warn!("Failed to record read of news {} by user {}: {}", id, user_id, e);
+            }
+        }
+        Ok(detail)
+    }
+
+    // 发表评论或回复；被回复的评论须属于同一新闻且可见
+    pub async fn add_comment(&self, news_id: i32, user_id: i32, data: CommentCreate) -> Result<CommentRow, NewsError> {
+        let content = validate_comment(&data.content)?;
+        if !self.news_dao.news_exists(news_id).await? {
+            return Err(NewsError::NotFound("News"));
+        }
+        let root_id = match data.parent_id {
+            Some(parent_id) => {
+                let parent = self
+                    .news_dao
+                    .get_comment(parent_id)
+                    .await?
+                    .filter(|c| c.news_id == news_id && c.is_visible())
+                    .ok_or(NewsError::NotFound("Comment"))?;
+                Some(parent.root_id.unwrap_or(parent.id))
+            }
+            None => None,
+        };
+        Ok(self
+            .news_dao
+            .create_comment(news_id, user_id, data.parent_id, root_id, content)
+            .await?)
+    }
+
+    // 新闻下的评论，按顶层评论分页，每条附带全部回复
+    pub async fn list_comments(&self, news_id: i32, query: PageQuery) -> Result<PaginatedComments<CommentNode>, NewsError> {
+        if !self.news_dao.news_exists(news_id).await? {
+            return Err(NewsError::NotFound("News"));
+        }
+        let (page, page_size) = page_params(query.page, query.page_size, 10)?;
+        Ok(self.news_dao.get_comment_threads(news_id, page, page_size).await?)
+    }
+
+    // 修改自己的评论，修改后重新进入审核队列；被隐藏的评论不能修改
+    pub async fn update_comment(&self, id: i32, user_id: i32, data: CommentUpdate) -> Result<CommentRow, NewsError> {
+        let content = validate_comment(&data.content)?;
+        let comment = self
+            .news_dao
+            .get_comment(id)
+            .await?
+            .filter(|c| c.deleted_at.is_none())
+            .ok_or(NewsError::NotFound("Comment"))?;
+        if comment.user_id != user_id {
+            return Err(NewsError::Forbidden("You can only edit your own comments".into()));
+        }
+        if comment.status == CommentStatus::Hidden {
+            return Err(NewsError::Forbidden("Comment has been hidden by a moderator".into()));
+        }
+        self.news_dao
+            .update_comment(id, content)
+            .await?
+            .ok_or(NewsError::NotFound("Comment"))
+    }
+
+    // 删除评论：作者本人或编辑可以删除，回复保留
+    pub async fn delete_comment(&self, id: i32, user_id: i32, role: UserRole) -> Result<(), NewsError> {
+        let comment = self
+            .news_dao
+            .get_comment(id)
+            .await?
+            .filter(|c| c.deleted_at.is_none())
+            .ok_or(NewsError::NotFound("Comment"))?;
+        if comment.user_id != user_id && role < UserRole::Editor {
+            return Err(NewsError::Forbidden("You can only delete your own comments".into()));
+        }
+        if self.news_dao.delete_comment(id).await? {
+            Ok(())
+        } else {
+            Err(NewsError::NotFound("Comment"))
+        }
+    }
+
+    // 审核队列，默认列出待审核的评论
+    pub async fn moderation_queue(&self, query: ModerationQuery) -> Result<PaginatedComments<CommentRow>, NewsError> {
+        let status = query.status.unwrap_or(CommentStatus::Pending);
+        let (page, page_size) = page_params(query.page, query.page_size, 20)?;
+        Ok(self.news_dao.get_comments_by_status(status, page, page_size).await?)
+    }
+
+    // 审核评论：通过或隐藏（编辑调用）
+    pub async fn moderate_comment(&self, id: i32, status: CommentStatus) -> Result<CommentRow, NewsError> {
+        self.news_dao
+            .set_comment_status(id, status)
+            .await?
+            .ok_or(NewsError::NotFound("Comment"))
+    }
 
     // 用户注册
     pub async fn register_user(
@@ -100,15 +700,17 @@ impl NewsService {
         }
 
         // 检查用户是否已存在
-        match self.users_repo.get_user_by_email(&data.email).await {
-            Ok(_) => return Err(NewsError::ValidationError("User already exists".into())),
-            Err(_) => {}
+        if self.users_repo.get_user_by_email(&data.email).await.is_ok() {
+            return Err(NewsError::Conflict { message: "User already exists".into(), constraint: None });
         }
 
+        let password_hash = hash_password(&data.password)
+            .map_err(|e| NewsError::PasswordHashError(e.to_string()))?;
+
         self.users_repo
-           .create_user(&data.name, &data.email, &data.password)
+           .create_user(&data.name, &data.email, &password_hash)
            .await
-           .map_err(NewsError::DbError)
+           .map_err(NewsError::from)
     }
 
     // 用户登录
@@ -116,16 +718,108 @@ impl NewsService {
         &self,
         data: UserLogin,
     ) -> Result<UserModel, NewsError> {
-        let user = self.users_repo.get_user_by_email(&data.email).await.map_err(|_| NewsError::UserNotFound)?;
-        if user.password != data.password {
-            return Err(NewsError::InvalidPassword);
+        // 用户不存在与密码错误返回相同的错误，避免暴露邮箱是否已注册
+        let invalid = || NewsError::Unauthorized("Invalid email or password".into());
+        let mut user = match self.users_repo.get_user_by_email(&data.email).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => return Err(invalid()),
+            Err(e) => return Err(e.into()),
+        };
+        match verify_password(&data.password, &user.password) {
+            PasswordCheck::Valid => {}
+            PasswordCheck::ValidLegacy => {
+                // 旧的明文密码：登录成功后透明地迁移为 Argon2 哈希，失败不影响本次登录
+                match hash_password(&data.password) {
+                    Ok(password_hash) => match self.users_repo.update_password(user.id, &password_hash).await {
+                        Ok(()) => user.password = password_hash,
+                        Err(e) => log::warn!("Failed to rehash legacy password for user {}: {}", user.id, e),
+                    },
+                    Err(e) => log::warn!("Failed to hash legacy password for user {}: {}", user.id, e),
+                }
+            }
+            PasswordCheck::Invalid => return Err(invalid()),
         }
         Ok(user)
     }
 
     // 新增公共方法来访问 users_repo
     pub async fn get_user_by_id(&self, user_id: i32) -> Result<UserModel, NewsError> {
-        self.users_repo.get_user_by_id(user_id).await.map_err(NewsError::DbError)
+        self.users_repo.get_user_by_id(user_id).await.map_err(|e| match e {
+            sqlx::Error::RowNotFound => NewsError::NotFound("User"),
+            e => e.into(),
+        })
+    }
+
+    // 用户订阅的分类
+    pub async fn get_subscriptions(&self, user_id: i32) -> Result<Vec<String>, NewsError> {
+        Ok(self.users_repo.get_subscriptions(user_id).await?)
+    }
+
+    // 整体替换订阅的分类，返回替换后的订阅
+    pub async fn set_subscriptions(&self, user_id: i32, data: SubscriptionsUpdate) -> Result<Vec<String>, NewsError> {
+        let mut categories = data
+            .categories
+            .iter()
+            .map(|c| normalize_category(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        categories.sort();
+        categories.dedup();
+        if categories.len() > MAX_SUBSCRIPTIONS {
+            return Err(NewsError::ValidationError(format!(
+                "At most {} categories can be subscribed",
+                MAX_SUBSCRIPTIONS
+            )));
+        }
+        self.users_repo.set_subscriptions(user_id, &categories).await?;
+        Ok(categories)
+    }
+
+    // 订阅单个分类，返回是否新增
+    pub async fn add_subscription(&self, user_id: i32, category: &str) -> Result<bool, NewsError> {
+        let category = normalize_category(category)?;
+        let current = self.users_repo.get_subscriptions(user_id).await?;
+        if current.contains(&category) {
+            return Ok(false);
+        }
+        if current.len() >= MAX_SUBSCRIPTIONS {
+            return Err(NewsError::ValidationError(format!(
+                "At most {} categories can be subscribed",
+                MAX_SUBSCRIPTIONS
+            )));
+        }
+        Ok(self.users_repo.add_subscription(user_id, &category).await?)
+    }
+
+    // 取消订阅单个分类
+    pub async fn remove_subscription(&self, user_id: i32, category: &str) -> Result<(), NewsError> {
+        if self.users_repo.remove_subscription(user_id, category.trim()).await? {
+            Ok(())
+        } else {
+            Err(NewsError::NotFound("Subscription"))
+        }
+    }
+
+    // 个性化信息流：只包含订阅分类的新闻，分页、排序、hide_read 等参数与新闻列表相同；
+    // 尚未订阅任何分类时返回全部新闻
+    pub async fn get_feed(&self, user_id: i32, mut query: NewsQuery) -> Result<PaginatedNews, NewsError> {
+        if query.category.is_some() {
+            return Err(NewsError::ValidationError(
+                "category is not supported on the feed, it is taken from your subscriptions".into(),
+            ));
+        }
+        let categories = self.users_repo.get_subscriptions(user_id).await?;
+        if !categories.is_empty() {
+            query.category = Some(categories.join(","));
+        }
+        self.get_paginated_for(query, Some(user_id)).await
+    }
+
+    // 修改用户角色（仅管理员调用）
+    pub async fn update_user_role(&self, user_id: i32, role: UserRole) -> Result<UserModel, NewsError> {
+        self.users_repo
+            .update_role(user_id, role)
+            .await?
+            .ok_or(NewsError::NotFound("User"))
     }
 }
 
@@ -133,28 +827,127 @@ impl NewsService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::pool::init_pool;
+    use crate::db::memory::MemoryStore;
     use crate::news::dao::NewsDao;
+    use chrono::Utc;
+
+    // 基于内存存储的 service，不依赖数据库；返回的 store 与 service 共享数据
+    fn memory_service() -> (NewsService, MemoryStore) {
+        let store = MemoryStore::new();
+        (NewsService::new(NewsDao::new(store.clone()), store.clone()), store)
+    }
+
+    fn news_item(category: &str, n: u32) -> NewsCreate {
+        NewsCreate {
+            news_type: category.to_string(),
+            href: format!("https://service.test/{}/{}", category, n),
+            title: format!("News {}", n),
+            content: "Content".into(),
+            published_at: None,
+        }
+    }
 
     #[actix_rt::test]
     async fn test_pagination_logic() {
-        let pool = init_pool().await.unwrap();
-        let news_repo = NewsRepo::new(pool.clone());
-        let news_dao = NewsDao::new(news_repo);
-        let users_repo = UsersRepo::new(pool.clone());
-        let service = NewsService::new(news_dao, users_repo);
+        let (service, _) = memory_service();
+        for n in 0..5 {
+            service.create_news(news_item("tech", n)).await.unwrap();
+        }
+        service.create_news(news_item("finance", 0)).await.unwrap();
+        let query = |params: &str| serde_urlencoded::from_str::<NewsQuery>(params).unwrap();
+
+        let page = service.get_paginated(query("category=tech&page=3&page_size=2")).await.unwrap();
+        assert_eq!(page.news.len(), 1);
+        assert_eq!(page.total_pages, Some(3));
+        assert_eq!(page.current_page, Some(3));
+
+        let all = service.get_paginated(query("page_size=10")).await.unwrap();
+        assert_eq!(all.news.len(), 6);
+
+        assert!(matches!(
+            service.get_paginated(query("from=2025-06-02&to=2025-06-01")).await,
+            Err(NewsError::ValidationError(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_page_size_bounds() {
+        let (service, _) = memory_service();
+        let news = service.create_news(news_item("tech", 0)).await.unwrap();
+        let query = |params: &str| serde_urlencoded::from_str::<NewsQuery>(params).unwrap();
+        let page = |page_size: u32| PageQuery { page: None, page_size: Some(page_size) };
+        let invalid = |result: Result<(), NewsError>| matches!(result, Err(NewsError::ValidationError(_)));
+
+        for page_size in [0, MAX_PAGE_SIZE + 1] {
+            let params = format!("page_size={}", page_size);
+            assert!(invalid(service.get_paginated(query(&params)).await.map(drop)));
+            assert!(invalid(service.get_paginated(query(&format!("{}&cursor=", params))).await.map(drop)));
+            let search = NewsSearchQuery { q: "News".into(), page: None, page_size: Some(page_size), category: None };
+            assert!(invalid(service.search_news(search).await.map(drop)));
+            assert!(invalid(service.get_bookmarks(1, page(page_size)).await.map(drop)));
+            assert!(invalid(service.get_history(1, page(page_size)).await.map(drop)));
+            assert!(invalid(service.list_comments(news.id, page(page_size)).await.map(drop)));
+            let moderation = ModerationQuery { status: None, page: None, page_size: Some(page_size) };
+            assert!(invalid(service.moderation_queue(moderation).await.map(drop)));
+        }
+
+        // 上限本身可用，page=0 按第一页处理
+        let list = service.get_paginated(query(&format!("page=0&page_size={}", MAX_PAGE_SIZE))).await.unwrap();
+        assert_eq!((list.news.len(), list.current_page), (1, Some(1)));
+    }
+
+    #[actix_rt::test]
+    async fn test_duplicate_href_is_conflict() {
+        let (service, _) = memory_service();
+
+        service.create_news(news_item("tech", 1)).await.unwrap();
+        match service.create_news(news_item("tech", 1)).await {
+            Err(NewsError::Conflict { constraint, .. }) => assert_eq!(constraint.as_deref(), Some("news_href_key")),
+            other => panic!("expected conflict, got {:?}", other),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_cursor_pagination() {
+        let (service, _) = memory_service();
+        let query = |params: String| serde_urlencoded::from_str::<NewsQuery>(&params).unwrap();
+
+        let category = "cursor";
+        for n in 0..3 {
+            service.create_news(news_item(category, n)).await.unwrap();
+        }
+
+        // 第一页：没有总页数，也没有上一页
+        let first = service.get_paginated(query(format!("category={}&page_size=2&cursor=", category))).await.unwrap();
+        assert_eq!(first.news.len(), 2);
+        assert!(first.total_pages.is_none() && first.prev_cursor.is_none());
+
+        let next = first.next_cursor.unwrap();
+        let second = service.get_paginated(query(format!("category={}&page_size=2&cursor={}", category, next))).await.unwrap();
+        assert_eq!(second.news.len(), 1);
+        assert!(second.next_cursor.is_none());
+
+        let prev = second.prev_cursor.unwrap();
+        let back = service.get_paginated(query(format!("category={}&page_size=2&cursor={}", category, prev))).await.unwrap();
+        assert_eq!(back.news.iter().map(|n| n.news.id).collect::<Vec<_>>(), first.news.iter().map(|n| n.news.id).collect::<Vec<_>>());
+        assert!(back.prev_cursor.is_none());
+
+        assert!(matches!(
+            service.get_paginated(query("cursor=bogus".into())).await,
+            Err(NewsError::ValidationError(_))
+        ));
+        assert!(matches!(
+            service.get_paginated(query("cursor=&sort=title".into())).await,
+            Err(NewsError::ValidationError(_))
+        ));
     }
 
     #[actix_rt::test]
     async fn test_user_register_and_login() {
-        let pool = init_pool().await.unwrap();
-        let news_repo = NewsRepo::new(pool.clone());
-        let news_dao = NewsDao::new(news_repo);
-        let users_repo = UsersRepo::new(pool.clone());
-        let service = NewsService::new(news_dao, users_repo);
+        let (service, _) = memory_service();
 
         // 生成唯一的邮箱地址，避免冲突
-        let timestamp = Utc::now().timestamp_nanos();
+        let timestamp = Utc::now().timestamp_nanos_opt().unwrap();
         let unique_email = format!("test_{}@example.com", timestamp);
 
         let register_data = UserRegister {
@@ -166,6 +959,7 @@ mod tests {
         // 测试用户注册
         let registered_user = service.register_user(register_data.clone()).await.unwrap();
         assert_eq!(registered_user.email, unique_email);
+        assert!(registered_user.password.starts_with("$argon2id$")); // 数据库中只保存哈希
 
         let login_data = UserLogin {
             email: unique_email.clone(), // 使用相同的唯一邮箱
@@ -175,5 +969,213 @@ mod tests {
         // 测试用户登录
         let logged_in_user = service.login_user(login_data).await.unwrap();
         assert_eq!(logged_in_user.email, unique_email);
+
+        // 重复注册返回冲突，不存在的用户与密码错误返回同样的错误
+        assert!(matches!(service.register_user(register_data).await, Err(NewsError::Conflict { .. })));
+        let unknown = service.login_user(UserLogin {
+            email: "nobody@example.com".to_string(),
+            password: "test_password".to_string(),
+        }).await;
+        assert!(matches!(unknown, Err(NewsError::Unauthorized(_))));
+    }
+
+    #[actix_rt::test]
+    async fn test_login_rehashes_legacy_password() {
+        let (service, users_repo) = memory_service();
+
+        // 模拟迁移前直接写入明文密码的旧记录
+        let unique_email = format!("legacy_{}@example.com", Utc::now().timestamp_nanos_opt().unwrap());
+        users_repo.create_user("legacy_user", &unique_email, "plain_password").await.unwrap();
+
+        let wrong = service.login_user(UserLogin {
+            email: unique_email.clone(),
+            password: "wrong_password".to_string(),
+        }).await;
+        assert!(matches!(wrong, Err(NewsError::Unauthorized(_))));
+
+        let user = service.login_user(UserLogin {
+            email: unique_email.clone(),
+            password: "plain_password".to_string(),
+        }).await.unwrap();
+        assert!(user.password.starts_with("$argon2id$"));
+
+        // 回写后的哈希仍可正常登录
+        let stored = users_repo.get_user_by_email(&unique_email).await.unwrap();
+        assert_eq!(stored.password, user.password);
+        assert!(service.login_user(UserLogin {
+            email: unique_email,
+            password: "plain_password".to_string(),
+        }).await.is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn test_bulk_ingest_counts() {
+        let (service, _) = memory_service();
+
+        let category = "bulk";
+        let item = |n: u32, title: &str| NewsCreate {
+            news_type: category.to_string(),
+            href: format!("https://bulk.test/{}/{}", category, n),
+            title: title.to_string(),
+            content: "Content".to_string(),
+            published_at: None,
+        };
+
+        let untyped = NewsCreate { news_type: String::new(), ..item(4, "D") };
+        let summary = service.bulk_ingest(vec![item(1, "A"), item(2, "B"), item(3, ""), untyped]).await;
+        assert_eq!(summary, IngestSummary { inserted: 2, updated: 0, unchanged: 0, failed: 2 });
+
+        // 重复导入：未变化的跳过，变化的更新
+        let summary = service.bulk_ingest(vec![item(1, "A"), item(2, "B2")]).await;
+        assert_eq!(summary, IngestSummary { inserted: 0, updated: 1, unchanged: 1, failed: 0 });
     }
-}
\ No newline at end of file
+
+    #[actix_rt::test]
+    async fn test_subscriptions_and_feed() {
+        let (service, store) = memory_service();
+        for n in 0..3 {
+            service.create_news(news_item("tech", n)).await.unwrap();
+            service.create_news(news_item("finance", n)).await.unwrap();
+        }
+        service.create_news(news_item("sports", 0)).await.unwrap();
+        let user = store.create_user("reader", "reader@example.com", "x").await.unwrap();
+        let query = |params: &str| serde_urlencoded::from_str::<NewsQuery>(params).unwrap();
+
+        // 未订阅时信息流包含全部新闻
+        assert_eq!(service.get_feed(user.id, query("page_size=20")).await.unwrap().news.len(), 7);
+
+        let subscribed = service
+            .set_subscriptions(user.id, SubscriptionsUpdate { categories: vec![" tech".into(), "finance".into(), "tech".into()] })
+            .await
+            .unwrap();
+        assert_eq!(subscribed, vec!["finance", "tech"]);
+        let feed = service.get_feed(user.id, query("page_size=4")).await.unwrap();
+        assert_eq!(feed.news.len(), 4);
+        assert_eq!(feed.total_pages, Some(2));
+        assert!(feed.news.iter().all(|n| n.news.news_type != "sports"));
+
+        // 单个分类的订阅与取消
+        assert!(service.add_subscription(user.id, "sports").await.unwrap());
+        assert!(!service.add_subscription(user.id, "sports").await.unwrap());
+        service.remove_subscription(user.id, "finance").await.unwrap();
+        assert!(matches!(service.remove_subscription(user.id, "finance").await, Err(NewsError::NotFound(_))));
+        assert_eq!(service.get_subscriptions(user.id).await.unwrap(), vec!["sports", "tech"]);
+        let feed = service.get_feed(user.id, query("page_size=20&cursor=")).await.unwrap();
+        assert_eq!(feed.news.len(), 4);
+
+        // 非法分类名与 category 参数
+        assert!(matches!(service.add_subscription(user.id, "a,b").await, Err(NewsError::ValidationError(_))));
+        assert!(matches!(service.add_subscription(user.id, "  ").await, Err(NewsError::ValidationError(_))));
+        assert!(matches!(service.get_feed(user.id, query("category=tech")).await, Err(NewsError::ValidationError(_))));
+    }
+
+    #[actix_rt::test]
+    async fn test_comment_threads_and_moderation() {
+        let (service, store) = memory_service();
+        let news = service.create_news(news_item("tech", 0)).await.unwrap();
+        let alice = store.create_user("alice", "alice@example.com", "x").await.unwrap();
+        let bob = store.create_user("bob", "bob@example.com", "x").await.unwrap();
+        let comment = |content: &str, parent_id: Option<i32>| CommentCreate { content: content.into(), parent_id };
+
+        let root = service.add_comment(news.id, alice.id, comment(" hello ", None)).await.unwrap();
+        assert_eq!(root.content, "hello");
+        let reply = service.add_comment(news.id, bob.id, comment("hi", Some(root.id))).await.unwrap();
+        let nested = service.add_comment(news.id, alice.id, comment("hi again", Some(reply.id))).await.unwrap();
+        assert_eq!(nested.root_id, Some(root.id));
+        let other = service.add_comment(news.id, bob.id, comment("newer thread", None)).await.unwrap();
+
+        let threads = service.list_comments(news.id, PageQuery { page: None, page_size: None }).await.unwrap();
+        assert_eq!(threads.total, 2);
+        assert_eq!(threads.comments.iter().map(|c| c.id).collect::<Vec<_>>(), vec![other.id, root.id]);
+        assert_eq!(threads.comments[1].replies[0].replies[0].id, nested.id);
+
+        // 校验与权限
+        assert!(matches!(service.add_comment(news.id, bob.id, comment("  ", None)).await, Err(NewsError::ValidationError(_))));
+        assert!(matches!(service.add_comment(999, bob.id, comment("x", None)).await, Err(NewsError::NotFound(_))));
+        assert!(matches!(
+            service.update_comment(root.id, bob.id, CommentUpdate { content: "mine".into() }).await,
+            Err(NewsError::Forbidden(_))
+        ));
+        assert!(matches!(service.delete_comment(root.id, bob.id, UserRole::Reader).await, Err(NewsError::Forbidden(_))));
+
+        // 删除的评论有回复时保留位置，隐藏最后一条可见回复后整个讨论串消失
+        service.delete_comment(root.id, alice.id, UserRole::Reader).await.unwrap();
+        service.moderate_comment(reply.id, CommentStatus::Hidden).await.unwrap();
+        let threads = service.list_comments(news.id, PageQuery { page: None, page_size: None }).await.unwrap();
+        let thread = &threads.comments[1];
+        assert!(thread.removed && thread.content.is_none());
+        assert!(thread.replies[0].removed);
+        assert_eq!(thread.replies[0].replies[0].content.as_deref(), Some("hi again"));
+        assert!(matches!(
+            service.update_comment(reply.id, bob.id, CommentUpdate { content: "edit".into() }).await,
+            Err(NewsError::Forbidden(_))
+        ));
+        assert!(matches!(service.add_comment(news.id, bob.id, comment("x", Some(reply.id))).await, Err(NewsError::NotFound(_))));
+
+        service.delete_comment(nested.id, bob.id, UserRole::Editor).await.unwrap();
+        let threads = service.list_comments(news.id, PageQuery { page: None, page_size: None }).await.unwrap();
+        assert_eq!(threads.total, 1);
+        assert_eq!(threads.comments.len(), 1);
+
+        // 审核队列：待审核的只剩 other
+        let queue = service.moderation_queue(ModerationQuery { status: None, page: None, page_size: None }).await.unwrap();
+        assert_eq!(queue.comments.iter().map(|c| c.id).collect::<Vec<_>>(), vec![other.id]);
+        service.moderate_comment(other.id, CommentStatus::Approved).await.unwrap();
+        assert_eq!(service.moderation_queue(ModerationQuery { status: None, page: None, page_size: None }).await.unwrap().total, 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_trending() {
+        let (service, store) = memory_service();
+        let fresh = service.create_news(news_item("tech", 0)).await.unwrap();
+        let liked = service.create_news(news_item("tech", 1)).await.unwrap();
+        let old = service
+            .create_news(NewsCreate { published_at: Some(Utc::now() - Duration::days(10)), ..news_item("tech", 2) })
+            .await
+            .unwrap();
+        let sports = service.create_news(news_item("sports", 3)).await.unwrap();
+        service.create_news(news_item("tech", 4)).await.unwrap(); // 没有浏览和点赞，不上榜
+        let user = store.create_user("reader", "reader@example.com", "x").await.unwrap();
+
+        for _ in 0..3 {
+            service.get_news_detail(fresh.id).await.unwrap();
+        }
+        for _ in 0..20 {
+            service.get_news_detail(old.id).await.unwrap();
+        }
+        service.get_news_detail(sports.id).await.unwrap();
+        service.react(user.id, liked.id, ReactionKind::Like).await.unwrap();
+        // 其他表态不计入热度
+        service.react(user.id, fresh.id, ReactionKind::Sad).await.unwrap();
+
+        let query = |category: Option<&str>, window: Option<&str>| TrendingQuery {
+            category: category.map(String::from),
+            window: window.map(String::from),
+            limit: None,
+        };
+        // 一次点赞折合多次浏览；发布较久的新闻即使浏览多也会衰减
+        let trending = service.trending(query(Some("tech"), None)).await.unwrap();
+        assert_eq!(trending.window, "24h");
+        assert_eq!(trending.news.iter().map(|n| n.news.id).collect::<Vec<_>>(), vec![liked.id, fresh.id, old.id]);
+        assert_eq!((trending.news[1].views, trending.news[1].likes), (3, 0));
+        assert!(trending.news.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let trending = service.trending(query(None, Some("7D"))).await.unwrap();
+        assert_eq!(trending.window, "7d");
+        assert_eq!(trending.news.len(), 4);
+        let trending = service.trending(TrendingQuery { limit: Some(1), ..query(None, None) }).await.unwrap();
+        assert_eq!(trending.news.len(), 1);
+
+        for window in ["0h", "31d", "7", "7天", "h", "-1d"] {
+            assert!(
+                matches!(service.trending(query(None, Some(window))).await, Err(NewsError::ValidationError(_))),
+                "{}",
+                window
+            );
+        }
+        assert!(matches!(
+            service.trending(TrendingQuery { limit: Some(0), ..query(None, None) }).await,
+            Err(NewsError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer};
+
+// 数据源（腾讯新闻）使用北京时间，不带时区的时间一律按 UTC+8 解释
+pub fn source_offset() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).expect("UTC+8 is a valid offset")
+}
+
+// 解析时间字符串：支持 RFC 3339（带时区），以及不带时区的
+// "YYYY-MM-DD HH:MM:SS[.f]"、"YYYY-MM-DDTHH:MM:SS[.f]"、"YYYY-MM-DD"（按 UTC+8）
+pub fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    let naive = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+    source_offset()
+        .from_local_datetime(&naive)
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+// serde 辅助：可选时间字段，格式同 parse_datetime
+pub fn deserialize_opt_datetime<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(s) if s.trim().is_empty() => Ok(None),
+        Some(s) => parse_datetime(&s)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid datetime: {}", s))),
+    }
+}
+
+// 在 news/time.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_naive_as_utc8() {
+        let dt = parse_datetime("2025-06-06 08:09:10").unwrap();
+        assert_eq!(dt.to_rfc3339(), "2025-06-06T00:09:10+00:00");
+
+        let dt = parse_datetime("2025-06-06 13:03:41.987475").unwrap();
+        assert_eq!(dt.format("%H:%M:%S").to_string(), "05:03:41");
+
+        let dt = parse_datetime("2025-06-06").unwrap();
+        assert_eq!(dt.to_rfc3339(), "2025-06-05T16:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_rfc3339_keeps_offset() {
+        let dt = parse_datetime("2025-06-06T08:09:10+08:00").unwrap();
+        assert_eq!(dt, parse_datetime("2025-06-06T00:09:10Z").unwrap());
+        assert!(parse_datetime("yesterday").is_none());
+    }
+}
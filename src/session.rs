@@ -0,0 +1,558 @@
+use std::collections::HashMap;
+
+use actix_session::config::{BrowserSession, PersistentSession, SessionLifecycle};
+use actix_session::storage::{
+    generate_session_key, CookieSessionStore, LoadError, SaveError, SessionKey, SessionStore, UpdateError,
+};
+use actix_session::SessionMiddleware;
+use actix_web::body::MessageBody;
+use actix_web::cookie::{time::Duration, Cookie, CookieJar, Key, SameSite};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderMap, HeaderValue, COOKIE, SET_COOKIE};
+use actix_web::middleware::Next;
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::config::{SessionConfig, SessionStoreKind};
+use crate::db::models::SessionModel;
+use crate::db::queries;
+
+type SessionState = HashMap<String, String>;
+
+// 登录时写入会话的键；会话被撤销后不能随其余状态一起保留
+const AUTH_KEYS: [&str; 2] = ["user_id", "sid"];
+
+// 会话 cookie 的密钥和属性；与 SessionMiddleware 使用同一份配置，供密钥轮换时补发 cookie
+#[derive(Clone)]
+pub struct SessionCookie {
+    name: String,
+    key: Key,
+    previous_keys: Vec<Key>,
+    secure: bool,
+    http_only: bool,
+    same_site: SameSite,
+    max_age: Option<Duration>,
+}
+
+impl SessionCookie {
+    pub fn new(config: &SessionConfig, key: Key) -> Self {
+        Self {
+            name: config.cookie_name.clone(),
+            key,
+            previous_keys: config.previous_signing_keys(),
+            secure: config.cookie_secure,
+            http_only: config.cookie_http_only,
+            same_site: config.same_site(),
+            max_age: config.max_age_secs.map(Duration::seconds),
+        }
+    }
+
+    pub fn has_previous_keys(&self) -> bool {
+        !self.previous_keys.is_empty()
+    }
+
+    // 按配置构建会话中间件：设置了 max_age 时为持久会话，否则为浏览器会话
+    pub fn middleware<S: SessionStore>(&self, store: S) -> SessionMiddleware<S> {
+        let lifecycle: SessionLifecycle = match self.max_age {
+            Some(ttl) => PersistentSession::default().session_ttl(ttl).into(),
+            None => BrowserSession::default().into(),
+        };
+        SessionMiddleware::builder(store, self.key.clone())
+            .cookie_name(self.name.clone())
+            .cookie_secure(self.secure)
+            .cookie_http_only(self.http_only)
+            .cookie_same_site(self.same_site)
+            .session_lifecycle(lifecycle)
+            .build()
+    }
+
+    // 请求中的会话 cookie 若由旧密钥加密，改写为当前密钥加密后的值，返回需要下发给客户端的新 cookie
+    fn rotate_request_cookie(&self, headers: &mut HeaderMap) -> Option<Cookie<'static>> {
+        let mut rotated = None;
+        let values: Vec<HeaderValue> = headers.get_all(COOKIE).cloned().collect();
+        let mut rewritten = Vec::with_capacity(values.len());
+        for value in values {
+            let Ok(text) = value.to_str() else {
+                rewritten.push(value);
+                continue;
+            };
+            let mut changed = false;
+            // 与 actix-web 解析 Cookie 请求头的方式一致
+            let pairs: Vec<String> = text
+                .split(';')
+                .map(str::trim)
+                .filter(|pair| !pair.is_empty())
+                .filter_map(|pair| Cookie::parse_encoded(pair).ok())
+                .map(|cookie| {
+                    if cookie.name() == self.name && rotated.is_none() {
+                        if let Some(fresh) = self.reencrypt(&cookie) {
+                            changed = true;
+                            let pair = Cookie::new(self.name.clone(), fresh.value().to_owned()).encoded().to_string();
+                            rotated = Some(fresh);
+                            return pair;
+                        }
+                    }
+                    cookie.stripped().encoded().to_string()
+                })
+                .collect();
+            match HeaderValue::from_str(&pairs.join("; ")) {
+                Ok(header) if changed => rewritten.push(header),
+                _ => rewritten.push(value),
+            }
+        }
+
+        if rotated.is_some() {
+            headers.remove(COOKIE);
+            for value in rewritten {
+                headers.append(COOKIE, value);
+            }
+        }
+        rotated
+    }
+
+    // 当前密钥无法解密、某个旧密钥可以解密时，返回用当前密钥重新加密并带上属性的 cookie
+    fn reencrypt(&self, cookie: &Cookie<'_>) -> Option<Cookie<'static>> {
+        let mut jar = CookieJar::new();
+        jar.add_original(cookie.clone().into_owned());
+        if jar.private(&self.key).get(&self.name).is_some() {
+            return None;
+        }
+        let plain = self.previous_keys.iter().find_map(|key| jar.private(key).get(&self.name))?;
+
+        let mut fresh = Cookie::new(self.name.clone(), plain.value().to_owned());
+        fresh.set_path("/");
+        fresh.set_secure(self.secure);
+        fresh.set_http_only(self.http_only);
+        fresh.set_same_site(self.same_site);
+        if let Some(max_age) = self.max_age {
+            fresh.set_max_age(max_age);
+        }
+        let mut jar = CookieJar::new();
+        jar.private_mut(&self.key).add(fresh);
+        jar.delta().next().cloned()
+    }
+}
+
+// 密钥轮换中间件，需放在 SessionMiddleware 外层：
+// 旧密钥加密的 cookie 在会话中间件解析前改写为新密钥加密；会话未变化时会话中间件不会下发 cookie，这里补发
+pub async fn rotate_session_key(
+    session_cookie: web::Data<SessionCookie>,
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let rotated = session_cookie.rotate_request_cookie(req.headers_mut());
+    let mut res = next.call(req).await?;
+
+    if let Some(cookie) = rotated {
+        let prefix = format!("{}=", session_cookie.name);
+        let already_set = res
+            .headers()
+            .get_all(SET_COOKIE)
+            .any(|value| value.to_str().is_ok_and(|v| v.starts_with(&prefix)));
+        if !already_set {
+            let value = HeaderValue::from_str(&cookie.encoded().to_string())
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+            res.headers_mut().append(SET_COOKIE, value);
+        }
+    }
+    Ok(res)
+}
+
+// 按配置选择会话存储；SessionMiddleware 只接受一种存储类型，因此用枚举分发
+pub enum AppSessionStore {
+    Cookie(CookieSessionStore),
+    Postgres(PgSessionStore),
+}
+
+impl AppSessionStore {
+    pub fn new(kind: SessionStoreKind, pool: &PgPool) -> Self {
+        match kind {
+            SessionStoreKind::Cookie => AppSessionStore::Cookie(CookieSessionStore::default()),
+            SessionStoreKind::Postgres => AppSessionStore::Postgres(PgSessionStore::new(pool.clone())),
+        }
+    }
+}
+
+impl SessionStore for AppSessionStore {
+    async fn load(&self, session_key: &SessionKey) -> Result<Option<SessionState>, LoadError> {
+        match self {
+            AppSessionStore::Cookie(store) => store.load(session_key).await,
+            AppSessionStore::Postgres(store) => store.load(session_key).await,
+        }
+    }
+
+    async fn save(&self, session_state: SessionState, ttl: &Duration) -> Result<SessionKey, SaveError> {
+        match self {
+            AppSessionStore::Cookie(store) => store.save(session_state, ttl).await,
+            AppSessionStore::Postgres(store) => store.save(session_state, ttl).await,
+        }
+    }
+
+    async fn update(
+        &self,
+        session_key: SessionKey,
+        session_state: SessionState,
+        ttl: &Duration,
+    ) -> Result<SessionKey, UpdateError> {
+        match self {
+            AppSessionStore::Cookie(store) => store.update(session_key, session_state, ttl).await,
+            AppSessionStore::Postgres(store) => store.update(session_key, session_state, ttl).await,
+        }
+    }
+
+    async fn update_ttl(&self, session_key: &SessionKey, ttl: &Duration) -> Result<(), anyhow::Error> {
+        match self {
+            AppSessionStore::Cookie(store) => store.update_ttl(session_key, ttl).await,
+            AppSessionStore::Postgres(store) => store.update_ttl(session_key, ttl).await,
+        }
+    }
+
+    async fn delete(&self, session_key: &SessionKey) -> Result<(), anyhow::Error> {
+        match self {
+            AppSessionStore::Cookie(store) => store.delete(session_key).await,
+            AppSessionStore::Postgres(store) => store.delete(session_key).await,
+        }
+    }
+}
+
+// Postgres 会话存储：状态保存在 sessions 表，cookie 中只有随机的会话 key
+#[derive(Clone)]
+pub struct PgSessionStore {
+    pool: PgPool,
+}
+
+impl PgSessionStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // 用户当前有效的会话
+    pub async fn list_user_sessions(&self, user_id: i32) -> Result<Vec<SessionModel>, sqlx::Error> {
+        queries::list_user_sessions(&self.pool, user_id).await
+    }
+
+    // 撤销用户的某个会话，返回是否存在该会话
+    pub async fn revoke(&self, user_id: i32, id: i64) -> Result<bool, sqlx::Error> {
+        queries::delete_user_session(&self.pool, user_id, id).await
+    }
+
+    // 撤销用户的全部会话，keep_sid 指定的会话除外，返回撤销数量
+    pub async fn revoke_all(&self, user_id: i32, keep_sid: Option<&str>) -> Result<u64, sqlx::Error> {
+        queries::delete_user_sessions(&self.pool, user_id, keep_sid).await
+    }
+
+    pub async fn purge_expired(&self) -> Result<u64, sqlx::Error> {
+        queries::delete_expired_sessions(&self.pool).await
+    }
+}
+
+// 会话状态中的值是 JSON 编码后的字符串，登录后 user_id 形如 "42"
+fn state_user_id(state: &SessionState) -> Option<i32> {
+    state.get("user_id").and_then(|v| v.parse().ok())
+}
+
+fn expires_at(ttl: &Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::seconds(ttl.whole_seconds())
+}
+
+impl SessionStore for PgSessionStore {
+    async fn load(&self, session_key: &SessionKey) -> Result<Option<SessionState>, LoadError> {
+        let state = queries::load_session(&self.pool, session_key.as_ref())
+            .await
+            .map_err(|e| LoadError::Other(e.into()))?;
+        state
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| LoadError::Deserialization(e.into()))
+    }
+
+    async fn save(&self, session_state: SessionState, ttl: &Duration) -> Result<SessionKey, SaveError> {
+        let state = serde_json::to_value(&session_state).map_err(|e| SaveError::Serialization(e.into()))?;
+        let session_key = generate_session_key();
+        queries::insert_session(
+            &self.pool,
+            session_key.as_ref(),
+            state_user_id(&session_state),
+            &state,
+            expires_at(ttl),
+        )
+        .await
+        .map_err(|e| SaveError::Other(e.into()))?;
+        Ok(session_key)
+    }
+
+    // 会话在本次请求期间被撤销或已过期时不复活旧会话：去掉登录信息后另存为新的匿名会话
+    async fn update(
+        &self,
+        session_key: SessionKey,
+        mut session_state: SessionState,
+        ttl: &Duration,
+    ) -> Result<SessionKey, UpdateError> {
+        let state = serde_json::to_value(&session_state).map_err(|e| UpdateError::Serialization(e.into()))?;
+        let updated = queries::update_session(
+            &self.pool,
+            session_key.as_ref(),
+            state_user_id(&session_state),
+            &state,
+            expires_at(ttl),
+        )
+        .await
+        .map_err(|e| UpdateError::Other(e.into()))?;
+        if updated {
+            return Ok(session_key);
+        }
+        for key in AUTH_KEYS {
+            session_state.remove(key);
+        }
+        self.save(session_state, ttl).await.map_err(|e| match e {
+            SaveError::Serialization(e) => UpdateError::Serialization(e),
+            SaveError::Other(e) => UpdateError::Other(e),
+        })
+    }
+
+    async fn update_ttl(&self, session_key: &SessionKey, ttl: &Duration) -> Result<(), anyhow::Error> {
+        queries::update_session_expiry(&self.pool, session_key.as_ref(), expires_at(ttl)).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, session_key: &SessionKey) -> Result<(), anyhow::Error> {
+        queries::delete_session(&self.pool, session_key.as_ref()).await?;
+        Ok(())
+    }
+}
+
+// 在 session.rs 底部添加以下测试代码
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SessionConfig;
+    use crate::db::fixture::TestDb;
+    use crate::db::repo::{NewsRepo, UsersRepo};
+    use crate::db::store::UserStore;
+    use crate::news::{dao::NewsDao, routes, service::NewsService};
+    use actix_session::Session;
+    use actix_web::middleware::{from_fn, Condition};
+    use actix_web::{http::StatusCode, test as actix_test, App, HttpResponse};
+    use serde_json::{json, Value};
+
+    fn session_cookie(key: &Key, previous: &[&Key]) -> SessionCookie {
+        let config = SessionConfig {
+            cookie_secure: false,
+            previous_keys: previous.iter().map(|k| crate::config::encode_key(k)).collect(),
+            ..SessionConfig::default()
+        };
+        SessionCookie::new(&config, key.clone())
+    }
+
+    async fn set_user(session: Session) -> HttpResponse {
+        session.insert("user_id", 7).unwrap();
+        HttpResponse::Ok().finish()
+    }
+
+    async fn get_user(session: Session) -> HttpResponse {
+        HttpResponse::Ok().json(session.get::<i32>("user_id").unwrap())
+    }
+
+    // 与 main.rs 相同的中间件顺序
+    macro_rules! cookie_app {
+        ($session_cookie:expr) => {
+            actix_test::init_service(
+                App::new()
+                    .wrap($session_cookie.middleware(CookieSessionStore::default()))
+                    .wrap(Condition::new($session_cookie.has_previous_keys(), from_fn(rotate_session_key)))
+                    .app_data(web::Data::new($session_cookie.clone()))
+                    .route("/set", web::get().to(set_user))
+                    .route("/get", web::get().to(get_user)),
+            )
+            .await
+        };
+    }
+
+    #[actix_rt::test]
+    async fn test_session_key_rotation() {
+        let (old_key, new_key) = (Key::generate(), Key::generate());
+
+        // 旧密钥下建立的会话
+        let old = session_cookie(&old_key, &[]);
+        let app = cookie_app!(old);
+        let res = actix_test::call_service(&app, actix_test::TestRequest::get().uri("/set").to_request()).await;
+        let cookie = res.response().cookies().next().expect("session cookie").into_owned();
+
+        // 只有新密钥时旧 cookie 失效
+        let rotated_out = session_cookie(&new_key, &[]);
+        let app = cookie_app!(rotated_out);
+        let req = actix_test::TestRequest::get().uri("/get").cookie(cookie.clone()).to_request();
+        let body: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body, Value::Null);
+
+        // 旧密钥作为 previous_keys 时仍可读取，并下发新密钥加密的 cookie
+        let rotating = session_cookie(&new_key, &[&old_key]);
+        let app = cookie_app!(rotating);
+        let req = actix_test::TestRequest::get().uri("/get").cookie(cookie.clone()).to_request();
+        let res = actix_test::call_service(&app, req).await;
+        let fresh = res.response().cookies().next().expect("re-issued cookie").into_owned();
+        assert_ne!(fresh.value(), cookie.value());
+        assert_eq!(fresh.http_only(), Some(true));
+        let body: Value = actix_test::read_body_json(res).await;
+        assert_eq!(body, json!(7));
+
+        // 新 cookie 不依赖旧密钥
+        let app = cookie_app!(rotated_out);
+        let req = actix_test::TestRequest::get().uri("/get").cookie(fresh).to_request();
+        let body: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body, json!(7));
+    }
+
+    #[actix_rt::test]
+    async fn test_pg_session_store() {
+        let db = TestDb::new().await;
+        let store = PgSessionStore::new(db.pool.clone());
+        let user = queries::create_user(&db.pool, "Reader", "reader@example.com", "x").await.unwrap();
+        let ttl = Duration::hours(1);
+
+        let mut state = SessionState::new();
+        state.insert("user_id".into(), user.id.to_string());
+        state.insert("sid".into(), "\"abc\"".into());
+        let key = store.save(state.clone(), &ttl).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), Some(state.clone()));
+
+        let sessions = store.list_user_sessions(user.id).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].sid.as_deref(), Some("abc"));
+
+        // 更新保留原 key
+        state.insert("theme".into(), "\"dark\"".into());
+        let same = store.update(key, state.clone(), &ttl).await.unwrap();
+        assert_eq!(store.load(&same).await.unwrap(), Some(state.clone()));
+
+        // 撤销后无法加载；撤销期间的更新另存为不带登录信息的新会话
+        assert!(store.revoke(user.id, sessions[0].id).await.unwrap());
+        assert!(!store.revoke(user.id, sessions[0].id).await.unwrap());
+        assert_eq!(store.load(&same).await.unwrap(), None);
+        let renewed = store.update(same, state.clone(), &ttl).await.unwrap();
+        let anonymous: SessionState = [("theme".to_string(), "\"dark\"".to_string())].into();
+        assert_eq!(store.load(&renewed).await.unwrap(), Some(anonymous));
+        assert!(store.list_user_sessions(user.id).await.unwrap().is_empty());
+
+        // 已过期的会话不加载，由 purge_expired 清理
+        let expired = store.save(state.clone(), &Duration::seconds(-1)).await.unwrap();
+        assert_eq!(store.load(&expired).await.unwrap(), None);
+        assert!(store.list_user_sessions(user.id).await.unwrap().is_empty());
+        assert_eq!(store.purge_expired().await.unwrap(), 1);
+
+        store.delete(&renewed).await.unwrap();
+        assert_eq!(store.load(&renewed).await.unwrap(), None);
+    }
+
+    // 在请求处理期间撤销当前会话，随后修改会话状态
+    async fn revoke_during_request(session: Session, store: web::Data<PgSessionStore>) -> HttpResponse {
+        let user_id = session.get::<i32>("user_id").unwrap().unwrap();
+        store.revoke_all(user_id, None).await.unwrap();
+        session.insert("theme", "dark").unwrap();
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_revoked_session_is_not_revived() {
+        let db = TestDb::new().await;
+        let store = PgSessionStore::new(db.pool.clone());
+        let service = NewsService::new(NewsDao::new(NewsRepo::new(db.pool.clone())), UsersRepo::new(db.pool.clone()));
+        let session_cookie = session_cookie(&Key::generate(), &[]);
+        let app = actix_test::init_service(
+            App::new()
+                .wrap(session_cookie.middleware(store.clone()))
+                .app_data(web::Data::new(service))
+                .app_data(web::Data::new(store.clone()))
+                .route("/revoke-during-request", web::post().to(revoke_during_request))
+                .configure(routes::config),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/user/register")
+            .set_json(json!({ "name": "Reader", "email": "reader@example.com", "password": "secret" }))
+            .to_request();
+        actix_test::call_service(&app, req).await;
+        let req = actix_test::TestRequest::post()
+            .uri("/user/login")
+            .set_json(json!({ "email": "reader@example.com", "password": "secret" }))
+            .to_request();
+        let res = actix_test::call_service(&app, req).await;
+        let cookie = res.response().cookies().next().expect("session cookie").into_owned();
+
+        let req = actix_test::TestRequest::post().uri("/revoke-during-request").cookie(cookie.clone()).to_request();
+        let res = actix_test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let reissued = res.response().cookies().next().expect("reissued cookie").into_owned();
+        assert_ne!(reissued.value(), cookie.value());
+
+        // 新下发的 cookie 与原 cookie 都不再是登录状态
+        for cookie in [reissued, cookie] {
+            let req = actix_test::TestRequest::get().uri("/user/check-login").cookie(cookie).to_request();
+            assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+        }
+        let user = UsersRepo::new(db.pool.clone()).get_user_by_email("reader@example.com").await.unwrap();
+        assert!(store.list_user_sessions(user.id).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_session_management_routes() {
+        let db = TestDb::new().await;
+        let store = PgSessionStore::new(db.pool.clone());
+        let service = NewsService::new(NewsDao::new(NewsRepo::new(db.pool.clone())), UsersRepo::new(db.pool.clone()));
+        let session_cookie = session_cookie(&Key::generate(), &[]);
+        let app = actix_test::init_service(
+            App::new()
+                .wrap(session_cookie.middleware(store.clone()))
+                .app_data(web::Data::new(service))
+                .app_data(web::Data::new(store.clone()))
+                .configure(routes::config),
+        )
+        .await;
+
+        let req = actix_test::TestRequest::post()
+            .uri("/user/register")
+            .set_json(json!({ "name": "Reader", "email": "reader@example.com", "password": "secret" }))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+        // 两台设备分别登录
+        let mut devices = Vec::new();
+        for _ in 0..2 {
+            let req = actix_test::TestRequest::post()
+                .uri("/user/login")
+                .set_json(json!({ "email": "reader@example.com", "password": "secret" }))
+                .to_request();
+            let res = actix_test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            devices.push(res.response().cookies().next().expect("session cookie").into_owned());
+        }
+
+        let req = actix_test::TestRequest::get().uri("/user/sessions").cookie(devices[1].clone()).to_request();
+        let sessions: Vec<Value> = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions.iter().filter(|s| s["current"] == json!(true)).count(), 1);
+        assert!(sessions.iter().all(|s| s.get("sid").is_none()));
+
+        // 第二台设备退出其他设备
+        let req = actix_test::TestRequest::delete().uri("/user/sessions").cookie(devices[1].clone()).to_request();
+        let body: Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body, json!({ "revoked": 1 }));
+
+        let req = actix_test::TestRequest::get().uri("/user/check-login").cookie(devices[0].clone()).to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+
+        // 撤销当前会话
+        let req = actix_test::TestRequest::get().uri("/user/sessions").cookie(devices[1].clone()).to_request();
+        let sessions: Vec<Value> = actix_test::call_and_read_body_json(&app, req).await;
+        let id = sessions[0]["id"].as_i64().unwrap();
+        let req = actix_test::TestRequest::delete()
+            .uri(&format!("/user/sessions/{}", id))
+            .cookie(devices[1].clone())
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::NO_CONTENT);
+        let req = actix_test::TestRequest::get().uri("/user/check-login").cookie(devices[1].clone()).to_request();
+        assert_eq!(actix_test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+    }
+}